@@ -0,0 +1,113 @@
+// Rewriting module by module, as `SystemIterator` did before symbols were
+// interned, against rewriting ids with `Compact`, on one thread and on all
+// of them.
+//
+// Run with `cargo bench`. Every case is run a few times and the fastest run
+// is reported.
+
+use l_system::parser;
+use l_system::system::{production, random, Compact, Production, State, System};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const RUNS: usize = 5;
+
+// A hash map lookup per module, and successors instantiated module by module.
+fn modules(system: &System, generations: usize) -> State {
+    let mut state = system.start().clone();
+    for generation in 0..generations {
+        let mut next = State::new();
+        for (index, module) in state.iter().enumerate() {
+            let sample = random::uniform(system.seed(), generation as u64, index as u64);
+            let productions = system.rules().get(&module.symbol).map(Vec::as_slice);
+            let bind = |p: &Production| p.bind(&state, &[], index, system.ignore());
+            match productions.and_then(|p| production::select(p, sample, |p| bind(p).is_some())) {
+                Some(p) => p.expand(&bind(p).unwrap_or_default(), &mut next),
+                None => next.push(module.clone()),
+            }
+        }
+        state = next;
+    }
+    state
+}
+
+fn ids(system: &System, generations: usize) -> Vec<u16> {
+    let mut compact = Compact::new(system.rules()).unwrap();
+    let mut state = compact.encode(system.start()).unwrap();
+    let mut buffer = Vec::new();
+    for generation in 0..generations {
+        buffer.clear();
+        compact.rewrite(&state, system.seed(), generation as u64, &mut buffer);
+        std::mem::swap(&mut state, &mut buffer);
+    }
+    state
+}
+
+fn ids_parallel(system: &System, generations: usize, threads: usize) -> Vec<u16> {
+    let mut compact = Compact::new(system.rules()).unwrap();
+    let mut state = compact.encode(system.start()).unwrap();
+    let mut buffer = Vec::new();
+    for generation in 0..generations {
+        compact.rewrite_parallel(
+            &state,
+            system.seed(),
+            generation as u64,
+            threads,
+            &mut buffer,
+        );
+        std::mem::swap(&mut state, &mut buffer);
+    }
+    state
+}
+
+fn fastest(mut run: impl FnMut()) -> Duration {
+    (0..RUNS)
+        .map(|_| {
+            let start = Instant::now();
+            run();
+            start.elapsed()
+        })
+        .min()
+        .unwrap()
+}
+
+fn main() {
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    println!(
+        "{:<24} {:>3} {:>10} {:>12} {:>12} {:>12} {:>12}",
+        "system",
+        "n",
+        "length",
+        "modules",
+        "ids",
+        format!("ids ({})", threads),
+        "iterator"
+    );
+    for (path, generations) in [("systems/branch.lsys", 9), ("systems/stochastic.lsys", 8)] {
+        let (_, system) = parser::parse(path).unwrap();
+        let length = ids(&system, generations).len();
+        let modules = fastest(|| {
+            black_box(modules(&system, generations));
+        });
+        let ids = fastest(|| {
+            black_box(ids(&system, generations));
+        });
+        let parallel = fastest(|| {
+            black_box(ids_parallel(&system, generations, threads));
+        });
+        // Rewrites ids, then decodes the last generation into modules.
+        let iterator = fastest(|| {
+            black_box(system.clone().into_iter().nth(generations));
+        });
+        println!(
+            "{:<24} {:>3} {:>10} {:>10.2}ms {:>10.2}ms {:>10.2}ms {:>10.2}ms",
+            path,
+            generations,
+            length,
+            modules.as_secs_f64() * 1000.0,
+            ids.as_secs_f64() * 1000.0,
+            parallel.as_secs_f64() * 1000.0,
+            iterator.as_secs_f64() * 1000.0
+        );
+    }
+}
@@ -0,0 +1,333 @@
+use l_system::export::{mesh, plotter};
+use l_system::export::{Mesh, Plotter, Png, Svg};
+use l_system::graphics::{Coloring, Config, Rgba, Visualizer};
+use l_system::parser;
+use l_system::system::{Module, System};
+use std::io::{self, Write};
+use std::path::Path;
+use std::process::ExitCode;
+use std::str::FromStr;
+
+const USAGE: &str = "\
+Usage: l_system <command> <file> [options]
+
+Commands:
+    expand <file>      Print the state of a generation
+    validate <file>    Parse the file and report errors
+    view <file>        Open the visualizer
+    render <file>      Render a generation to an image (.svg, .png), plot it
+                       (.gcode, .hpgl) or mesh it (.obj, .stl, .ply)
+
+Options:
+    -n, --iterations <n>    Generation to expand or render (default: 0)
+    -o, --output <path>     Output file of `render`
+    -s, --seed <seed>       Override the seed of the file
+    -j, --threads <n>       Threads to rewrite generations on (default: 1)
+    --size <w>x<h>          Size of the rendered image in pixels
+                            (default: 800x800 for .png, fitted for .svg)
+    --background <colour>   Background of the rendered image, `#rrggbb[aa]`
+                            (default: white for .png, none for .svg)
+    --supersampling <n>     Samples per pixel side of a .png (default: 3)
+    --page <w>x<h>          Page of a plot in mm (default: 297x210)
+    --margin <mm>           Margin of a plot (default: 15)
+    --feed <mm/min>         Feed rate of a plot, pen down (default: 1500)
+    --travel-feed <mm/min>  Feed rate of a plot, pen up (default: 3000)
+    --pen-up <gcode>        Command lifting the pen (default: `G0 Z5`)
+    --pen-down <gcode>      Command lowering the pen (default: `G0 Z0`)
+    --sides <n>             Sides of the tubes of a mesh (default: 8)
+    --ascii                 Write an .stl as text rather than binary
+    -h, --help              Print this message
+
+Pass `-` as the file to read the system from stdin.";
+
+const SUCCESS: u8 = 0;
+const FAILURE: u8 = 1;
+const USAGE_ERROR: u8 = 2;
+
+enum Command {
+    Expand,
+    Validate,
+    View,
+    Render,
+}
+
+struct Options {
+    command: Command,
+    file: String,
+    iterations: usize,
+    output: Option<String>,
+    seed: Option<u64>,
+    threads: usize,
+    size: Option<(u32, u32)>,
+    background: Option<Rgba>,
+    supersampling: Option<u32>,
+    plotter: Plotter,
+    mesh: Mesh,
+    ascii: bool,
+}
+
+pub fn run(args: impl Iterator<Item = String>) -> ExitCode {
+    let args: Vec<String> = args.collect();
+    if args.is_empty() {
+        println!("{}", USAGE);
+        return ExitCode::from(USAGE_ERROR);
+    }
+
+    let options = match parse_args(&args) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return ExitCode::from(SUCCESS);
+        }
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, USAGE);
+            return ExitCode::from(USAGE_ERROR);
+        }
+    };
+
+    match execute(options) {
+        Ok(()) => ExitCode::from(SUCCESS),
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::from(FAILURE)
+        }
+    }
+}
+
+// The options of a command, or `None` when help was asked for.
+fn parse_args(args: &[String]) -> Result<Option<Options>, String> {
+    let command = match args[0].as_str() {
+        "-h" | "--help" => return Ok(None),
+        "expand" => Command::Expand,
+        "validate" => Command::Validate,
+        "view" => Command::View,
+        "render" => Command::Render,
+        other => return Err(format!("Unknown command `{}`.", other)),
+    };
+
+    let mut file = None;
+    let mut iterations = 0;
+    let mut output = None;
+    let mut seed = None;
+    let mut threads = 1;
+    let mut size = None;
+    let mut background = None;
+    let mut supersampling = None;
+    let mut plotter = Plotter::default();
+    let mut mesh = Mesh::default();
+    let mut ascii = false;
+
+    let mut args = args[1..].iter();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("Missing value for `{}`.", arg))
+        };
+        match arg.as_str() {
+            "-n" | "--iterations" => {
+                iterations = value()?
+                    .parse()
+                    .map_err(|_| "`--iterations` should be a non-negative integer.".to_string())?
+            }
+            "-o" | "--output" => output = Some(value()?.clone()),
+            "-s" | "--seed" => {
+                seed = Some(
+                    value()?
+                        .parse()
+                        .map_err(|_| "`--seed` should be u64.".to_string())?,
+                )
+            }
+            "-j" | "--threads" => {
+                threads = value()?
+                    .parse()
+                    .ok()
+                    .filter(|&n| n > 0)
+                    .ok_or_else(|| "`--threads` should be a positive integer.".to_string())?
+            }
+            "--size" => size = Some(dimensions(value()?, "--size")?),
+            "--background" => background = Some(value()?.parse::<Rgba>()?),
+            "--supersampling" => {
+                supersampling =
+                    Some(value()?.parse().ok().filter(|&n| n > 0).ok_or_else(|| {
+                        "`--supersampling` should be a positive integer.".to_string()
+                    })?)
+            }
+            "--page" => plotter.page = dimensions(value()?, "--page")?,
+            "--margin" => {
+                plotter.margin = value()?
+                    .parse()
+                    .ok()
+                    .filter(|&m| m >= 0.0)
+                    .ok_or_else(|| "`--margin` should be a non-negative number.".to_string())?
+            }
+            "--feed" => plotter.draw_feed = feed(value()?, "--feed")?,
+            "--travel-feed" => plotter.travel_feed = feed(value()?, "--travel-feed")?,
+            "--pen-up" => plotter.pen_up = value()?.clone(),
+            "--pen-down" => plotter.pen_down = value()?.clone(),
+            "--sides" => {
+                mesh.sides =
+                    value()?.parse().ok().filter(|&n| n >= 3).ok_or_else(|| {
+                        "`--sides` should be an integer of at least 3.".to_string()
+                    })?
+            }
+            "--ascii" => ascii = true,
+            "-h" | "--help" => return Ok(None),
+            flag if flag.starts_with('-') && flag != "-" => {
+                return Err(format!("Unknown option `{}`.", flag))
+            }
+            path if file.is_none() => file = Some(path.to_string()),
+            extra => return Err(format!("Unexpected argument `{}`.", extra)),
+        }
+    }
+
+    let file = file.ok_or_else(|| "Missing the `.lsys` file.".to_string())?;
+    if let (Command::Render, None) = (&command, &output) {
+        return Err("`render` needs an `--output` file.".to_string());
+    }
+
+    let options = Options {
+        command,
+        file,
+        iterations,
+        output,
+        seed,
+        threads,
+        size,
+        background,
+        supersampling,
+        plotter,
+        mesh,
+        ascii,
+    };
+    if options.output.as_deref().and_then(extension).as_deref() == Some("png") {
+        png(&options).check()?;
+    }
+    Ok(Some(options))
+}
+
+// A `<width>x<height>` pair of positive numbers.
+fn dimensions<T: FromStr + PartialOrd + Default>(
+    value: &str,
+    flag: &str,
+) -> Result<(T, T), String> {
+    value
+        .split_once('x')
+        .and_then(|(w, h)| Some((w.parse().ok()?, h.parse().ok()?)))
+        .filter(|(w, h)| *w > T::default() && *h > T::default())
+        .ok_or_else(|| format!("`{}` should be `<width>x<height>`.", flag))
+}
+
+fn feed(value: &str, flag: &str) -> Result<f32, String> {
+    value
+        .parse()
+        .ok()
+        .filter(|&f: &f32| f > 0.0)
+        .ok_or_else(|| format!("`{}` should be a positive number.", flag))
+}
+
+fn load(options: &Options) -> Result<(Config, System), String> {
+    let parsed = match options.file.as_str() {
+        "-" => parser::parse_reader(io::stdin().lock()),
+        file => parser::parse(file),
+    };
+    let (config, system) = parsed.map_err(|e| e.render())?;
+    let system = system
+        .with_threads(options.threads)
+        .with_births(config.coloring == Coloring::Generation);
+    match options.seed {
+        Some(seed) => Ok((config, system.with_seed(seed))),
+        None => Ok((config, system)),
+    }
+}
+
+fn execute(options: Options) -> Result<(), String> {
+    let (config, system) = load(&options)?;
+
+    match options.command {
+        Command::Validate => println!("{}: ok", options.file),
+        Command::Expand => print_modules(system.stream(options.iterations))
+            .map_err(|e| format!("error: Could not write the state: {}", e))?,
+        Command::View => {
+            let visualizer = Visualizer::new("L-Systems", (640, 480), system, config);
+            match options.file.as_str() {
+                "-" => visualizer,
+                file => visualizer.watch(file, options.seed),
+            }
+            .run()
+        }
+        Command::Render => {
+            let modules = system.stream(options.iterations);
+            let output = options.output.clone().unwrap_or_default();
+            let saved = match extension(&output).as_deref() {
+                Some("svg") => svg(&options).save(modules, &config, &output),
+                Some("png") => png(&options).save(modules, &config, &output),
+                Some("gcode" | "nc" | "gc") => Plotter {
+                    format: plotter::Format::Gcode,
+                    ..options.plotter.clone()
+                }
+                .save(modules, &config, &output),
+                Some("hpgl" | "plt") => Plotter {
+                    format: plotter::Format::Hpgl,
+                    ..options.plotter.clone()
+                }
+                .save(modules, &config, &output),
+                Some("obj") => mesh(&options, mesh::Format::Obj).save(modules, &config, &output),
+                Some("stl") => {
+                    let format = match options.ascii {
+                        true => mesh::Format::StlAscii,
+                        false => mesh::Format::StlBinary,
+                    };
+                    mesh(&options, format).save(modules, &config, &output)
+                }
+                Some("ply") => mesh(&options, mesh::Format::Ply).save(modules, &config, &output),
+                _ => return Err(format!("error: Unsupported output format: `{}`.", output)),
+            };
+            saved.map_err(|e| format!("error: Could not write `{}`: {}", output, e))?
+        }
+    }
+
+    Ok(())
+}
+
+// The lowercase extension of `path`, which picks the format of a render.
+fn extension(path: &str) -> Option<String> {
+    Path::new(path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(str::to_lowercase)
+}
+
+fn svg(options: &Options) -> Svg {
+    Svg {
+        size: options.size.map(|(w, h)| (w as f32, h as f32)),
+        background: options.background.map(|b| b.to_string()),
+        ..Svg::default()
+    }
+}
+
+fn png(options: &Options) -> Png {
+    let mut png = Png::default();
+    if let Some((width, height)) = options.size {
+        (png.width, png.height) = (width, height);
+    }
+    png.background = options.background.unwrap_or(png.background);
+    png.supersampling = options.supersampling.unwrap_or(png.supersampling);
+    png
+}
+
+fn mesh(options: &Options, format: mesh::Format) -> Mesh {
+    Mesh {
+        format,
+        ..options.mesh.clone()
+    }
+}
+
+// Written as they come, the state of a deep generation may not fit in memory.
+fn print_modules(modules: impl Iterator<Item = Module>) -> io::Result<()> {
+    let mut stdout = io::BufWriter::new(io::stdout().lock());
+    for module in modules {
+        write!(stdout, "{}", module)?;
+    }
+    writeln!(stdout)?;
+    stdout.flush()
+}
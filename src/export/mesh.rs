@@ -0,0 +1,450 @@
+use crate::graphics::polygon;
+use crate::graphics::turtle::{self, Point, Primitive};
+use crate::graphics::{Config, Rgba};
+use crate::system::Module;
+use std::borrow::Borrow;
+use std::f32::consts::PI;
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+
+/// The file format a [`Mesh`] writes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Obj,
+    StlAscii,
+    StlBinary,
+    Ply,
+}
+
+/// Turns a state into a triangle mesh, for 3D printing or rendering
+/// elsewhere.
+///
+/// Lines are swept into tubes of `sides` sides, as wide as the turtle's
+/// thickness. Chained lines make a single tube, bent at every joint and
+/// tapering where the thickness changes. Dots become spheres and polygons
+/// slabs `leaf_thickness` thick, or single faces if that is 0. Every tube,
+/// sphere and slab is closed, so that slicers can print the union of them.
+///
+/// Coordinates are in turtle units. OBJ and PLY keep the turtle's y-up
+/// axes; STL, which slicers read as z-up, has the plant standing on z. With
+/// a palette, or polygons, OBJ and PLY also carry vertex colours, white
+/// where there is no colour; STL has none.
+#[derive(Debug, Clone)]
+pub struct Mesh {
+    pub format: Format,
+    pub sides: usize,
+    pub leaf_thickness: f32,
+}
+
+impl Default for Mesh {
+    fn default() -> Self {
+        Self {
+            format: Format::Obj,
+            sides: 8,
+            leaf_thickness: 0.2,
+        }
+    }
+}
+
+/// Triangles as indices into `vertices`, counterclockwise seen from outside,
+/// and the colour of every vertex.
+#[derive(Debug, Clone, Default)]
+pub struct Geometry {
+    pub vertices: Vec<Point>,
+    pub colors: Vec<Option<Rgba>>,
+    pub triangles: Vec<[u32; 3]>,
+}
+
+// Chained lines of a colour waiting to be swept into a tube.
+#[derive(Debug, Default)]
+struct Chain {
+    points: Vec<Point>,
+    widths: Vec<f32>,
+    color: Option<Rgba>,
+}
+
+#[derive(Debug, Default)]
+struct Builder {
+    sides: usize,
+    leaf_thickness: f32,
+    geometry: Geometry,
+    chain: Chain,
+    // Of the vertices being added.
+    color: Option<Rgba>,
+}
+
+impl Builder {
+    fn new(mesh: &Mesh) -> Self {
+        Self {
+            sides: mesh.sides.max(3),
+            leaf_thickness: mesh.leaf_thickness,
+            ..Self::default()
+        }
+    }
+
+    fn add(&mut self, primitive: Primitive) {
+        match primitive {
+            Primitive::Segment {
+                from,
+                to,
+                width,
+                color,
+                ..
+            } => self.line(from, to, width, color),
+            Primitive::Dot { at, radius, color } => {
+                self.color = color;
+                self.sphere(at, radius)
+            }
+            Primitive::Polygon { vertices, color } => {
+                self.color = Some(color);
+                self.slab(&vertices)
+            }
+        }
+    }
+
+    fn finish(mut self) -> Geometry {
+        self.sweep();
+        self.geometry
+    }
+
+    fn line(&mut self, from: Point, to: Point, width: f32, color: Option<Rgba>) {
+        if (to - from).length() < 1e-6 || width <= 0.0 {
+            return;
+        }
+        let chain = &self.chain;
+        // Turning back on itself, a tube would fold into its own joint.
+        let continues = match chain.points.as_slice() {
+            [.., before, last] => {
+                (*last - from).length() < 1e-4
+                    && (*last - *before).dot(to - from) >= 0.0
+                    && chain.color == color
+            }
+            _ => false,
+        };
+        if !continues {
+            self.sweep();
+            self.chain.points.push(from);
+            self.chain.color = color;
+        }
+        self.chain.points.push(to);
+        self.chain.widths.push(width);
+    }
+
+    // Sweeps the current chain into a capped tube.
+    fn sweep(&mut self) {
+        let Chain {
+            points,
+            widths,
+            color,
+        } = std::mem::take(&mut self.chain);
+        if points.len() < 2 {
+            return;
+        }
+        self.color = color;
+        let direction = |i: usize| (points[i + 1] - points[i]).normalized();
+        let mut normal = perpendicular(direction(0));
+        let mut rings = Vec::with_capacity(points.len());
+        for (i, &point) in points.iter().enumerate() {
+            // Joints are cut along the bisector of the lines they join.
+            let tangent = match i {
+                0 => direction(0),
+                i if i == points.len() - 1 => direction(i - 1),
+                i => (direction(i - 1) + direction(i)).normalized(),
+            };
+            // The normal is carried along, so that the tube doesn't twist.
+            normal = (normal - tangent * normal.dot(tangent)).normalized();
+            if !normal.x.is_finite() {
+                normal = perpendicular(tangent);
+            }
+            let radius = widths[i.min(widths.len() - 1)] / 2.0;
+            let ring = self.ring(point, tangent, normal, radius);
+            if i > 0 && i < points.len() - 1 {
+                self.miter(&ring, point, direction(i - 1), tangent);
+            }
+            rings.push(ring);
+        }
+        let start = self.vertex(points[0]);
+        let end = self.vertex(points[points.len() - 1]);
+        self.tube(&rings, start, end);
+    }
+
+    fn sphere(&mut self, center: Point, radius: f32) {
+        if radius <= 0.0 {
+            return;
+        }
+        let (up, normal) = (Point::xyz(0.0, 0.0, 1.0), Point::xyz(1.0, 0.0, 0.0));
+        let latitudes = (self.sides / 2).max(2);
+        let rings: Vec<_> = (1..latitudes)
+            .map(|k| {
+                let angle = -PI / 2.0 + PI * k as f32 / latitudes as f32;
+                let at = center + up * (radius * angle.sin());
+                self.ring(at, up, normal, radius * angle.cos())
+            })
+            .collect();
+        let south = self.vertex(center - up * radius);
+        let north = self.vertex(center + up * radius);
+        self.tube(&rings, south, north);
+    }
+
+    fn slab(&mut self, outline: &[Point]) {
+        // Newell's normal, the outline turns counterclockwise around it.
+        let normal = (0..outline.len())
+            .map(|i| {
+                let (a, b) = (outline[i], outline[(i + 1) % outline.len()]);
+                Point::xyz(
+                    (a.y - b.y) * (a.z + b.z),
+                    (a.z - b.z) * (a.x + b.x),
+                    (a.x - b.x) * (a.y + b.y),
+                )
+            })
+            .fold(Point::default(), |sum, n| sum + n);
+        if normal.length() < 1e-9 {
+            return;
+        }
+        let normal = normal.normalized();
+        let offset = normal * (self.leaf_thickness / 2.0);
+        let faces: Vec<[usize; 3]> = polygon::triangulate(outline)
+            .into_iter()
+            .map(|[a, b, c]| {
+                let facing = (outline[b] - outline[a]).cross(outline[c] - outline[a]);
+                match facing.dot(normal) < 0.0 {
+                    true => [a, c, b],
+                    false => [a, b, c],
+                }
+            })
+            .collect();
+
+        let top: Vec<_> = outline.iter().map(|&p| self.vertex(p + offset)).collect();
+        for &[a, b, c] in &faces {
+            self.triangle(top[a], top[b], top[c]);
+        }
+        if self.leaf_thickness <= 0.0 {
+            return;
+        }
+        let bottom: Vec<_> = outline.iter().map(|&p| self.vertex(p - offset)).collect();
+        for &[a, b, c] in &faces {
+            self.triangle(bottom[a], bottom[c], bottom[b]);
+        }
+        self.band(&bottom, &top);
+    }
+
+    // `sides` points around `center`, counterclockwise seen from `tangent`.
+    fn ring(&mut self, center: Point, tangent: Point, normal: Point, radius: f32) -> Vec<u32> {
+        let binormal = tangent.cross(normal);
+        (0..self.sides)
+            .map(|i| {
+                let angle = 2.0 * PI * i as f32 / self.sides as f32;
+                let offset = normal * angle.cos() + binormal * angle.sin();
+                self.vertex(center + offset * radius)
+            })
+            .collect()
+    }
+
+    // Stretches the `ring` of a joint at `center`, cut along the bisector
+    // `tangent` of lines going `direction` and on, to where the tube meets
+    // the cut: by 1 / cos(θ / 2) across the turn, θ being its angle.
+    // Chains break on turns over 90°, so it's at most √2.
+    fn miter(&mut self, ring: &[u32], center: Point, direction: Point, tangent: Point) {
+        let across = direction - tangent * direction.dot(tangent);
+        if across.length() < 1e-6 {
+            return;
+        }
+        let across = across.normalized();
+        let stretch = 1.0 / direction.dot(tangent) - 1.0;
+        for &index in ring {
+            let vertex = &mut self.geometry.vertices[index as usize];
+            let offset = *vertex - center;
+            *vertex = *vertex + across * (offset.dot(across) * stretch);
+        }
+    }
+
+    // Joins consecutive rings, and closes the ends with fans around `start`
+    // and `end`.
+    fn tube(&mut self, rings: &[Vec<u32>], start: u32, end: u32) {
+        for pair in rings.windows(2) {
+            self.band(&pair[0], &pair[1]);
+        }
+        let (first, last) = (&rings[0], &rings[rings.len() - 1]);
+        for i in 0..first.len() {
+            let next = (i + 1) % first.len();
+            self.triangle(start, first[next], first[i]);
+            self.triangle(end, last[i], last[next]);
+        }
+    }
+
+    // The quads between two loops turning the same way.
+    fn band(&mut self, from: &[u32], to: &[u32]) {
+        for i in 0..from.len() {
+            let next = (i + 1) % from.len();
+            self.triangle(from[i], from[next], to[next]);
+            self.triangle(from[i], to[next], to[i]);
+        }
+    }
+
+    fn vertex(&mut self, point: Point) -> u32 {
+        self.geometry.vertices.push(point);
+        self.geometry.colors.push(self.color);
+        (self.geometry.vertices.len() - 1) as u32
+    }
+
+    fn triangle(&mut self, a: u32, b: u32, c: u32) {
+        self.geometry.triangles.push([a, b, c]);
+    }
+}
+
+impl Mesh {
+    /// Builds the mesh as `modules` are walked, keeping its vertices and
+    /// triangles rather than the modules (see
+    /// [`Stream`](crate::system::Stream) for when those are expanded whole).
+    pub fn render<M: Borrow<Module>>(
+        &self,
+        modules: impl IntoIterator<Item = M>,
+        config: &Config,
+    ) -> Vec<u8> {
+        let mut builder = Builder::new(self);
+        turtle::walk(modules, config, |primitive| builder.add(primitive));
+        self.write(&builder.finish())
+    }
+
+    pub fn render_primitives(&self, primitives: &[Primitive]) -> Vec<u8> {
+        self.write(&self.geometry(primitives))
+    }
+
+    pub fn geometry(&self, primitives: &[Primitive]) -> Geometry {
+        let mut builder = Builder::new(self);
+        primitives.iter().for_each(|p| builder.add(p.clone()));
+        builder.finish()
+    }
+
+    pub fn save<M: Borrow<Module>>(
+        &self,
+        modules: impl IntoIterator<Item = M>,
+        config: &Config,
+        path: &str,
+    ) -> io::Result<()> {
+        fs::write(path, self.render(modules, config))
+    }
+
+    fn write(&self, geometry: &Geometry) -> Vec<u8> {
+        match self.format {
+            Format::Obj => obj(geometry).into_bytes(),
+            Format::StlAscii => stl_ascii(&z_up(geometry)).into_bytes(),
+            Format::StlBinary => stl_binary(&z_up(geometry)),
+            Format::Ply => ply(geometry).into_bytes(),
+        }
+    }
+}
+
+// A quarter turn around x, which keeps the triangles turning the same way.
+fn z_up(geometry: &Geometry) -> Geometry {
+    Geometry {
+        vertices: geometry
+            .vertices
+            .iter()
+            .map(|p| Point::xyz(p.x, -p.z, p.y))
+            .collect(),
+        colors: geometry.colors.clone(),
+        triangles: geometry.triangles.clone(),
+    }
+}
+
+fn obj(geometry: &Geometry) -> String {
+    let mut out = String::new();
+    let colored = geometry.colors.iter().any(Option::is_some);
+    for (v, color) in geometry.vertices.iter().zip(&geometry.colors) {
+        let _ = write!(out, "v {} {} {}", v.x, v.y, v.z);
+        // Colours after the position are a common extension, from 0 to 1.
+        if colored {
+            let [r, g, b] = rgb(*color).map(|c| c as f32 / 255.0);
+            let _ = write!(out, " {} {} {}", r, g, b);
+        }
+        out.push('\n');
+    }
+    // Indices start at 1.
+    for [a, b, c] in &geometry.triangles {
+        let _ = writeln!(out, "f {} {} {}", a + 1, b + 1, c + 1);
+    }
+    out
+}
+
+fn ply(geometry: &Geometry) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "ply\nformat ascii 1.0");
+    let _ = writeln!(out, "element vertex {}", geometry.vertices.len());
+    let _ = writeln!(out, "property float x\nproperty float y\nproperty float z");
+    let colored = geometry.colors.iter().any(Option::is_some);
+    if colored {
+        let _ = writeln!(
+            out,
+            "property uchar red\nproperty uchar green\nproperty uchar blue"
+        );
+    }
+    let _ = writeln!(out, "element face {}", geometry.triangles.len());
+    let _ = writeln!(out, "property list uchar int vertex_indices\nend_header");
+    for (v, color) in geometry.vertices.iter().zip(&geometry.colors) {
+        let _ = write!(out, "{} {} {}", v.x, v.y, v.z);
+        if colored {
+            let [r, g, b] = rgb(*color);
+            let _ = write!(out, " {} {} {}", r, g, b);
+        }
+        out.push('\n');
+    }
+    for [a, b, c] in &geometry.triangles {
+        let _ = writeln!(out, "3 {} {} {}", a, b, c);
+    }
+    out
+}
+
+fn stl_ascii(geometry: &Geometry) -> String {
+    let mut out = String::from("solid l_system\n");
+    for (normal, corners) in facets(geometry) {
+        let _ = writeln!(out, "facet normal {} {} {}", normal.x, normal.y, normal.z);
+        let _ = writeln!(out, "  outer loop");
+        for v in corners {
+            let _ = writeln!(out, "    vertex {} {} {}", v.x, v.y, v.z);
+        }
+        let _ = writeln!(out, "  endloop\nendfacet");
+    }
+    out.push_str("endsolid l_system\n");
+    out
+}
+
+fn stl_binary(geometry: &Geometry) -> Vec<u8> {
+    let mut out = vec![0; 80];
+    out.extend_from_slice(&(geometry.triangles.len() as u32).to_le_bytes());
+    for (normal, corners) in facets(geometry) {
+        for p in [normal].iter().chain(&corners) {
+            for c in [p.x, p.y, p.z] {
+                out.extend_from_slice(&c.to_le_bytes());
+            }
+        }
+        // No attributes.
+        out.extend_from_slice(&[0, 0]);
+    }
+    out
+}
+
+fn facets(geometry: &Geometry) -> impl Iterator<Item = (Point, [Point; 3])> + '_ {
+    geometry.triangles.iter().map(|triangle| {
+        let [a, b, c] = triangle.map(|i| geometry.vertices[i as usize]);
+        let normal = (b - a).cross(c - a);
+        let normal = match normal.length() > 0.0 {
+            true => normal.normalized(),
+            false => normal,
+        };
+        (normal, [a, b, c])
+    })
+}
+
+fn rgb(color: Option<Rgba>) -> [u8; 3] {
+    color.map_or([255; 3], |c| [c.r, c.g, c.b])
+}
+
+fn perpendicular(direction: Point) -> Point {
+    let axis = match direction.x.abs() < 0.9 {
+        true => Point::xyz(1.0, 0.0, 0.0),
+        false => Point::xyz(0.0, 1.0, 0.0),
+    };
+    direction.cross(axis).normalized()
+}
@@ -0,0 +1,10 @@
+pub mod mesh;
+pub mod plotter;
+pub mod png;
+mod raster;
+pub mod svg;
+
+pub use mesh::Mesh;
+pub use plotter::Plotter;
+pub use png::Png;
+pub use svg::Svg;
@@ -0,0 +1,362 @@
+use crate::graphics::turtle::{self, Point, Primitive};
+use crate::graphics::{Config, Rgba};
+use crate::system::Module;
+use std::borrow::Borrow;
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+
+/// The language a [`Plotter`] writes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Gcode,
+    Hpgl,
+}
+
+/// Plots a state with a pen plotter, as G-code or HPGL.
+///
+/// Chained lines are merged into strokes drawn without lifting the pen, and
+/// strokes are reordered, and possibly reversed, so that the pen travels as
+/// little as possible while up. Moves without drawing (`f`) only show up as
+/// travel between strokes. Dots are plotted as a touch of the pen and
+/// polygons as their outline, as a pen can't fill.
+///
+/// Each colour of the palette, and the fill colour of polygons, is plotted
+/// with its own pen, one after the other. HPGL selects pens by number, and
+/// G-code pauses with `M0` for the pen to be swapped.
+///
+/// Lengths are in millimetres and feed rates in millimetres per minute. The
+/// drawing is scaled and centered to fit the page within `margin`, with the
+/// origin at the bottom left corner of the page. `pen_up` and `pen_down` are
+/// the G-code commands that lift and lower the pen; HPGL has its own.
+#[derive(Debug, Clone)]
+pub struct Plotter {
+    pub format: Format,
+    pub page: (f32, f32),
+    pub margin: f32,
+    pub draw_feed: f32,
+    pub travel_feed: f32,
+    pub pen_up: String,
+    pub pen_down: String,
+    pub optimize: bool,
+}
+
+impl Default for Plotter {
+    fn default() -> Self {
+        Self {
+            format: Format::Gcode,
+            // A4, landscape.
+            page: (297.0, 210.0),
+            margin: 15.0,
+            draw_feed: 1500.0,
+            travel_feed: 3000.0,
+            pen_up: "G0 Z5".to_string(),
+            pen_down: "G0 Z0".to_string(),
+            optimize: true,
+        }
+    }
+}
+
+type Stroke = Vec<(f32, f32)>;
+
+// The strokes of a pen, `None` being the one without a palette colour.
+type Pen = (Option<Rgba>, Vec<Stroke>);
+
+#[derive(Debug, Default)]
+struct Strokes {
+    strokes: Vec<(Option<Rgba>, Stroke)>,
+}
+
+impl Strokes {
+    // The page is y-up like the turtle, z is dropped.
+    fn add(&mut self, primitive: Primitive) {
+        let flat = |p: &Point| (p.x, p.y);
+        match primitive {
+            Primitive::Segment {
+                from, to, color, ..
+            } => {
+                let (from, to) = (flat(&from), flat(&to));
+                match self.strokes.last_mut() {
+                    Some((pen, stroke))
+                        if *pen == color && stroke.last().is_some_and(|&last| meet(last, from)) =>
+                    {
+                        stroke.push(to)
+                    }
+                    _ => self.strokes.push((color, vec![from, to])),
+                }
+            }
+            Primitive::Dot { at, color, .. } => self.strokes.push((color, vec![flat(&at)])),
+            Primitive::Polygon { vertices, color } => {
+                let mut outline: Stroke = vertices.iter().map(flat).collect();
+                outline.push(outline[0]);
+                self.strokes.push((Some(color), outline));
+            }
+        }
+    }
+}
+
+impl Plotter {
+    /// Chains the lines of `modules` into strokes as they are walked. Every
+    /// stroke is kept, to be sorted by pen, though not the modules, unless
+    /// the [`Stream`](crate::system::Stream) isn't expanded depth-first.
+    pub fn render<M: Borrow<Module>>(
+        &self,
+        modules: impl IntoIterator<Item = M>,
+        config: &Config,
+    ) -> String {
+        let mut strokes = Strokes::default();
+        turtle::walk(modules, config, |primitive| strokes.add(primitive));
+        self.write(self.place(strokes.strokes))
+    }
+
+    pub fn render_primitives(&self, primitives: &[Primitive]) -> String {
+        self.write(self.pens(primitives))
+    }
+
+    /// The strokes plotted, in order and in page coordinates.
+    pub fn strokes(&self, primitives: &[Primitive]) -> Vec<Stroke> {
+        let pens = self.pens(primitives);
+        pens.into_iter().flat_map(|(_, strokes)| strokes).collect()
+    }
+
+    fn pens(&self, primitives: &[Primitive]) -> Vec<Pen> {
+        let mut strokes = Strokes::default();
+        primitives.iter().for_each(|p| strokes.add(p.clone()));
+        self.place(strokes.strokes)
+    }
+
+    pub fn save<M: Borrow<Module>>(
+        &self,
+        modules: impl IntoIterator<Item = M>,
+        config: &Config,
+        path: &str,
+    ) -> io::Result<()> {
+        fs::write(path, self.render(modules, config))
+    }
+
+    // Fits the strokes to the page, then sorts them by pen, in the order the
+    // pens are first used, and orders those of each pen from where the last
+    // one stopped.
+    fn place(&self, mut strokes: Vec<(Option<Rgba>, Stroke)>) -> Vec<Pen> {
+        let points = || strokes.iter().flat_map(|(_, stroke)| stroke);
+        let min = points().fold((f32::INFINITY, f32::INFINITY), |m, p| {
+            (m.0.min(p.0), m.1.min(p.1))
+        });
+        let max = points().fold((f32::NEG_INFINITY, f32::NEG_INFINITY), |m, p| {
+            (m.0.max(p.0), m.1.max(p.1))
+        });
+        let area = (
+            (self.page.0 - 2.0 * self.margin).max(0.0),
+            (self.page.1 - 2.0 * self.margin).max(0.0),
+        );
+        let size = (max.0 - min.0, max.1 - min.1);
+        let scale = match (size.0 > 0.0, size.1 > 0.0) {
+            (true, true) => (area.0 / size.0).min(area.1 / size.1),
+            (true, false) => area.0 / size.0,
+            (false, true) => area.1 / size.1,
+            (false, false) => 1.0,
+        };
+        let center = ((min.0 + max.0) / 2.0, (min.1 + max.1) / 2.0);
+        for point in strokes.iter_mut().flat_map(|(_, stroke)| stroke) {
+            *point = (
+                self.page.0 / 2.0 + (point.0 - center.0) * scale,
+                self.page.1 / 2.0 + (point.1 - center.1) * scale,
+            );
+        }
+
+        let mut pens: Vec<Pen> = Vec::new();
+        for (color, stroke) in strokes {
+            match pens.iter_mut().find(|(pen, _)| *pen == color) {
+                Some((_, strokes)) => strokes.push(stroke),
+                None => pens.push((color, vec![stroke])),
+            }
+        }
+        if self.optimize {
+            let mut at = (0.0, 0.0);
+            for (_, strokes) in &mut pens {
+                *strokes = join(order(std::mem::take(strokes), at));
+                at = strokes.last().map_or(at, |s| s[s.len() - 1]);
+            }
+        }
+        pens
+    }
+
+    fn write(&self, pens: Vec<Pen>) -> String {
+        match self.format {
+            Format::Gcode => self.gcode(&pens),
+            Format::Hpgl => self.hpgl(&pens),
+        }
+    }
+
+    fn gcode(&self, pens: &[Pen]) -> String {
+        let mut out = String::new();
+        let (width, height) = self.page;
+        let _ = writeln!(out, "; {}x{}mm", width, height);
+        let _ = writeln!(out, "G21 ; millimetres");
+        let _ = writeln!(out, "G90 ; absolute");
+        let _ = writeln!(out, "{}", self.pen_up);
+        let travel = |out: &mut String, (x, y): (f32, f32)| {
+            let _ = writeln!(out, "G1 X{:.3} Y{:.3} F{}", x, y, self.travel_feed);
+        };
+        for (pen, strokes) in pens {
+            if pens.len() > 1 {
+                let color = pen.map_or("default".to_string(), |c| c.to_string());
+                let _ = writeln!(out, "M0 ; pen {}", color);
+            }
+            for stroke in strokes {
+                travel(&mut out, stroke[0]);
+                let _ = writeln!(out, "{}", self.pen_down);
+                for (i, (x, y)) in stroke.iter().enumerate().skip(1) {
+                    let _ = match i {
+                        1 => writeln!(out, "G1 X{:.3} Y{:.3} F{}", x, y, self.draw_feed),
+                        _ => writeln!(out, "G1 X{:.3} Y{:.3}", x, y),
+                    };
+                }
+                let _ = writeln!(out, "{}", self.pen_up);
+            }
+        }
+        travel(&mut out, (0.0, 0.0));
+        out
+    }
+
+    fn hpgl(&self, pens: &[Pen]) -> String {
+        // Plotter units are 40 per millimetre, speeds in centimetres per second.
+        let unit = |(x, y): (f32, f32)| format!("{},{}", (x * 40.0).round(), (y * 40.0).round());
+        let mut out = String::new();
+        let _ = writeln!(out, "IN;SP1;VS{};", self.draw_feed / 600.0);
+        for (number, (_, strokes)) in pens.iter().enumerate() {
+            if number > 0 {
+                let _ = writeln!(out, "SP{};", number + 1);
+            }
+            for stroke in strokes {
+                let _ = write!(out, "PU{};PD", unit(stroke[0]));
+                let points: Vec<_> = stroke.iter().skip(1).map(|&p| unit(p)).collect();
+                let _ = writeln!(out, "{};", points.join(","));
+            }
+        }
+        let _ = writeln!(out, "PU0,0;SP0;");
+        out
+    }
+}
+
+/// The distance the pen travels up, from the origin and back, to plot
+/// `strokes` in order.
+pub fn travel(strokes: &[Stroke]) -> f32 {
+    let mut at = (0.0, 0.0);
+    let mut distance = 0.0;
+    for stroke in strokes {
+        distance += length(at, stroke[0]);
+        at = stroke[stroke.len() - 1];
+    }
+    distance + length(at, (0.0, 0.0))
+}
+
+fn length(a: (f32, f32), b: (f32, f32)) -> f32 {
+    (a.0 - b.0).hypot(a.1 - b.1)
+}
+
+fn meet(a: (f32, f32), b: (f32, f32)) -> bool {
+    length(a, b) < 1e-4
+}
+
+// Greedily plots next the stroke with an end closest to the pen, reversed if
+// that is its last point. Ends are looked up in a grid, from the cell of the
+// pen outwards, so that this stays fast on thousands of strokes.
+fn order(strokes: Vec<Stroke>, start: (f32, f32)) -> Vec<Stroke> {
+    if strokes.len() < 2 {
+        return strokes;
+    }
+    let ends = |i: usize| [strokes[i][0], strokes[i][strokes[i].len() - 1]];
+    let (min, max) = (0..strokes.len()).flat_map(ends).fold(
+        (
+            (f32::INFINITY, f32::INFINITY),
+            (f32::NEG_INFINITY, f32::NEG_INFINITY),
+        ),
+        |(min, max), p| {
+            (
+                (min.0.min(p.0), min.1.min(p.1)),
+                (max.0.max(p.0), max.1.max(p.1)),
+            )
+        },
+    );
+    let side = (strokes.len() as f32).sqrt().ceil() as usize;
+    let cell = ((max.0 - min.0).max(max.1 - min.1) / side as f32).max(1e-3);
+    let index = |p: (f32, f32)| {
+        let column = ((p.0 - min.0) / cell).floor().clamp(0.0, (side - 1) as f32) as usize;
+        let row = ((p.1 - min.1) / cell).floor().clamp(0.0, (side - 1) as f32) as usize;
+        (column, row)
+    };
+
+    // The strokes with an end in each cell, as `(stroke, reversed)`.
+    let mut grid = vec![Vec::<(usize, bool)>::new(); side * side];
+    for i in 0..strokes.len() {
+        for (end, point) in ends(i).into_iter().enumerate() {
+            let (column, row) = index(point);
+            grid[row * side + column].push((i, end == 1));
+        }
+    }
+
+    let mut ordered = Vec::with_capacity(strokes.len());
+    let mut at = start;
+    for _ in 0..strokes.len() {
+        let (column, row) = index(at);
+        let mut best: Option<(f32, usize, bool)> = None;
+        for ring in 0..side {
+            let (low_c, high_c) = (column.saturating_sub(ring), (column + ring).min(side - 1));
+            let (low_r, high_r) = (row.saturating_sub(ring), (row + ring).min(side - 1));
+            for r in low_r..=high_r {
+                for c in low_c..=high_c {
+                    // Only the border of the ring is new.
+                    if r.abs_diff(row) != ring && c.abs_diff(column) != ring {
+                        continue;
+                    }
+                    for &(i, reversed) in &grid[r * side + c] {
+                        let end = ends(i)[reversed as usize];
+                        let distance = length(at, end);
+                        if best.is_none_or(|(d, ..)| distance < d) {
+                            best = Some((distance, i, reversed));
+                        }
+                    }
+                }
+            }
+            // Ends further out are at least `ring` cells away.
+            if best.is_some_and(|(d, ..)| d <= ring as f32 * cell) {
+                break;
+            }
+        }
+
+        let Some((_, i, reversed)) = best else { break };
+        for point in ends(i) {
+            let (column, row) = index(point);
+            grid[row * side + column].retain(|&(j, _)| j != i);
+        }
+        at = ends(i)[!reversed as usize];
+        ordered.push((i, reversed));
+    }
+
+    let mut strokes: Vec<Option<Stroke>> = strokes.into_iter().map(Some).collect();
+    ordered
+        .into_iter()
+        .filter_map(|(i, reversed)| {
+            let mut stroke = strokes[i].take()?;
+            if reversed {
+                stroke.reverse();
+            }
+            Some(stroke)
+        })
+        .collect()
+}
+
+// Merges strokes that start where the previous one ends.
+fn join(strokes: Vec<Stroke>) -> Vec<Stroke> {
+    let mut joined: Vec<Stroke> = Vec::with_capacity(strokes.len());
+    for stroke in strokes {
+        match joined.last_mut() {
+            Some(last) if stroke.len() > 1 && meet(last[last.len() - 1], stroke[0]) => {
+                last.extend_from_slice(&stroke[1..])
+            }
+            _ => joined.push(stroke),
+        }
+    }
+    joined
+}
@@ -0,0 +1,260 @@
+use crate::export::raster::Canvas;
+use crate::graphics::turtle::{self, Primitive};
+use crate::graphics::{polygon, Bounds, Config, Rgba, Transform};
+use crate::system::Module;
+use std::borrow::Borrow;
+use std::fs;
+use std::io;
+
+/// Rasterizes a state to an anti-aliased PNG image, without opening a window.
+///
+/// The drawing is scaled and centered to fit a `width` by `height` image,
+/// leaving `padding` pixels on every side. Line widths and dot radii are in
+/// pixels, as in the visualizer, and every pixel averages `supersampling`
+/// squared samples. As in SVGs, fills are drawn before lines, and `stroke`
+/// is the colour of lines and dots when the system has no palette.
+#[derive(Debug, Clone)]
+pub struct Png {
+    pub width: u32,
+    pub height: u32,
+    pub padding: f32,
+    pub background: Rgba,
+    pub stroke: Rgba,
+    pub supersampling: u32,
+}
+
+impl Default for Png {
+    fn default() -> Self {
+        Self {
+            width: 800,
+            height: 800,
+            padding: 10.0,
+            background: Rgba::new(255, 255, 255, 255),
+            stroke: Rgba::new(0, 0, 0, 255),
+            supersampling: 3,
+        }
+    }
+}
+
+impl Png {
+    /// The most samples a canvas may hold, a GiB of RGBA.
+    pub const MAX_SAMPLES: u64 = 1 << 28;
+
+    /// An error unless the image has pixels and, once supersampled, fits in
+    /// [`MAX_SAMPLES`](Self::MAX_SAMPLES).
+    pub fn check(&self) -> Result<(), String> {
+        if self.width == 0 || self.height == 0 {
+            return Err(format!(
+                "A {}x{} image has no pixels to render.",
+                self.width, self.height
+            ));
+        }
+        let side = |pixels: u32| (pixels as u64).checked_mul(self.supersampling.max(1) as u64);
+        let samples = side(self.width)
+            .zip(side(self.height))
+            .and_then(|(width, height)| width.checked_mul(height));
+        match samples {
+            Some(samples) if samples <= Self::MAX_SAMPLES => Ok(()),
+            _ => Err(format!(
+                "A {}x{} image with a supersampling of {} is too large to render.",
+                self.width, self.height, self.supersampling
+            )),
+        }
+    }
+
+    /// Walks `modules` twice, first for the bounds of the drawing, then to
+    /// draw it straight onto the canvas, so only the polygons, to be filled
+    /// under the lines, are kept. A [`Stream`](crate::system::Stream) is
+    /// expanded twice.
+    ///
+    /// Fails when the image doesn't pass [`check`](Self::check).
+    pub fn render<M: Borrow<Module>>(
+        &self,
+        modules: impl IntoIterator<Item = M> + Clone,
+        config: &Config,
+    ) -> Result<Vec<u8>, String> {
+        self.check()?;
+        let mut bounds: Option<Bounds> = None;
+        let mut polygons = Vec::new();
+        turtle::walk(modules.clone(), config, |primitive| {
+            if let Some(b) = Bounds::of_primitive(&primitive) {
+                bounds = Some(bounds.map_or(b, |bounds| bounds.union(b)));
+            }
+            if let Primitive::Polygon { .. } = primitive {
+                polygons.push(primitive);
+            }
+        });
+
+        let (mut canvas, transform) = self.canvas(bounds);
+        polygons
+            .iter()
+            .for_each(|p| fill(&mut canvas, &transform, p));
+        turtle::walk(modules, config, |p| self.draw(&mut canvas, &transform, &p));
+        Ok(encode(self.width, self.height, &canvas.pixels()))
+    }
+
+    pub fn render_primitives(&self, primitives: &[Primitive]) -> Result<Vec<u8>, String> {
+        let pixels = self.rasterize(primitives)?;
+        Ok(encode(self.width, self.height, &pixels))
+    }
+
+    /// The image as 8 bit RGBA, row by row, before it is encoded.
+    ///
+    /// Fails when the image doesn't pass [`check`](Self::check).
+    pub fn rasterize(&self, primitives: &[Primitive]) -> Result<Vec<u8>, String> {
+        self.check()?;
+        let (mut canvas, transform) = self.canvas(Bounds::of(primitives));
+        for primitive in primitives {
+            fill(&mut canvas, &transform, primitive);
+        }
+        for primitive in primitives {
+            self.draw(&mut canvas, &transform, primitive);
+        }
+        Ok(canvas.pixels())
+    }
+
+    // A blank canvas, and the transform fitting `bounds` to it.
+    fn canvas(&self, bounds: Option<Bounds>) -> (Canvas, Transform) {
+        let (width, height) = (self.width as usize, self.height as usize);
+        let canvas = Canvas::new(width, height, self.supersampling as usize, self.background);
+        let transform = match bounds {
+            Some(bounds) => Transform::fit(&bounds, width as f32, height as f32, self.padding),
+            None => Transform::translate(width as f32 / 2.0, height as f32 / 2.0),
+        };
+        (canvas, transform)
+    }
+
+    // Lines and dots, polygons are filled beforehand.
+    fn draw(&self, canvas: &mut Canvas, transform: &Transform, primitive: &Primitive) {
+        match primitive {
+            Primitive::Segment {
+                from,
+                to,
+                width,
+                color,
+                ..
+            } => canvas.line(
+                transform.apply(*from),
+                transform.apply(*to),
+                *width,
+                color.unwrap_or(self.stroke),
+            ),
+            Primitive::Dot { at, radius, color } => {
+                canvas.disc(transform.apply(*at), *radius, color.unwrap_or(self.stroke))
+            }
+            Primitive::Polygon { .. } => {}
+        }
+    }
+
+    pub fn save<M: Borrow<Module>>(
+        &self,
+        modules: impl IntoIterator<Item = M> + Clone,
+        config: &Config,
+        path: &str,
+    ) -> io::Result<()> {
+        let png = self
+            .render(modules, config)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        fs::write(path, png)
+    }
+}
+
+fn fill(canvas: &mut Canvas, transform: &Transform, primitive: &Primitive) {
+    if let Primitive::Polygon { vertices, color } = primitive {
+        for [a, b, c] in polygon::triangulate(vertices) {
+            let (a, b, c) = (vertices[a], vertices[b], vertices[c]);
+            canvas.triangle(
+                transform.apply(a),
+                transform.apply(b),
+                transform.apply(c),
+                *color,
+            );
+        }
+    }
+}
+
+/// Encodes 8 bit RGBA `pixels`, row by row, as a PNG image.
+///
+/// The image data goes in uncompressed deflate blocks, which keeps the
+/// encoder short at the cost of larger files.
+pub fn encode(width: u32, height: u32, pixels: &[u8]) -> Vec<u8> {
+    let row = width as usize * 4;
+    // Every row starts with its filter, none.
+    let mut data = Vec::with_capacity(pixels.len() + height as usize);
+    for line in pixels.chunks(row.max(1)).take(height as usize) {
+        data.push(0);
+        data.extend_from_slice(line);
+    }
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&width.to_be_bytes());
+    header.extend_from_slice(&height.to_be_bytes());
+    // 8 bits per channel, RGBA, deflate, adaptive filtering, no interlacing.
+    header.extend_from_slice(&[8, 6, 0, 0, 0]);
+
+    let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+    chunk(&mut png, b"IHDR", &header);
+    chunk(&mut png, b"IDAT", &zlib(&data));
+    chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+fn chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+    let crc = crc32(kind.iter().chain(data));
+    png.extend_from_slice(&crc.to_be_bytes());
+}
+
+// A zlib stream of stored deflate blocks.
+fn zlib(data: &[u8]) -> Vec<u8> {
+    const BLOCK: usize = 65535;
+    let mut stream = vec![0x78, 0x01];
+    let blocks = data.chunks(BLOCK).collect::<Vec<_>>();
+    if blocks.is_empty() {
+        stream.extend_from_slice(&[1, 0, 0, 0xff, 0xff]);
+    }
+    for (i, block) in blocks.iter().enumerate() {
+        let last = i + 1 == blocks.len();
+        let length = block.len() as u16;
+        stream.push(last as u8);
+        stream.extend_from_slice(&length.to_le_bytes());
+        stream.extend_from_slice(&(!length).to_le_bytes());
+        stream.extend_from_slice(block);
+    }
+    stream.extend_from_slice(&adler32(data).to_be_bytes());
+    stream
+}
+
+fn crc32<'a>(bytes: impl Iterator<Item = &'a u8>) -> u32 {
+    let mut table = [0u32; 256];
+    for (n, entry) in table.iter_mut().enumerate() {
+        let mut c = n as u32;
+        for _ in 0..8 {
+            c = match c & 1 {
+                1 => 0xedb8_8320 ^ (c >> 1),
+                _ => c >> 1,
+            };
+        }
+        *entry = c;
+    }
+    !bytes.fold(!0u32, |crc, &byte| {
+        table[((crc ^ byte as u32) & 0xff) as usize] ^ (crc >> 8)
+    })
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+    const MOD: u32 = 65521;
+    let (mut a, mut b) = (1u32, 0u32);
+    // Sums of this many bytes can't overflow before being reduced.
+    for chunk in bytes.chunks(5552) {
+        for &byte in chunk {
+            a += byte as u32;
+            b += a;
+        }
+        a %= MOD;
+        b %= MOD;
+    }
+    (b << 16) | a
+}
@@ -0,0 +1,174 @@
+// Software rasterization, for exports that can't open a window.
+//
+// A canvas is drawn at `supersampling` times the resolution of the image,
+// then every block of samples is averaged into a pixel. Lines and dots also
+// fade out over the width of a sample at their edges, so they stay smooth
+// without supersampling; polygons only rely on it.
+//
+// Coordinates are in pixels, with y pointing down.
+
+use crate::graphics::Rgba;
+
+pub struct Canvas {
+    width: usize,
+    height: usize,
+    supersampling: usize,
+    // Straight RGBA, `width * supersampling` samples per row.
+    samples: Vec<[u8; 4]>,
+}
+
+impl Canvas {
+    pub fn new(width: usize, height: usize, supersampling: usize, background: Rgba) -> Self {
+        let supersampling = supersampling.max(1);
+        let count = width * height * supersampling * supersampling;
+        Self {
+            width,
+            height,
+            supersampling,
+            samples: vec![[background.r, background.g, background.b, background.a]; count],
+        }
+    }
+
+    /// A line of `width` pixels with round caps.
+    pub fn line(&mut self, from: (f32, f32), to: (f32, f32), width: f32, color: Rgba) {
+        if width <= 0.0 {
+            return;
+        }
+        let (from, to) = (self.sample(from), self.sample(to));
+        let radius = width * self.supersampling as f32 / 2.0;
+        let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+        let length = dx * dx + dy * dy;
+
+        let min = (from.0.min(to.0) - radius, from.1.min(to.1) - radius);
+        let max = (from.0.max(to.0) + radius, from.1.max(to.1) + radius);
+        self.cover(min, max, color, |x, y| {
+            // The distance to the closest point of the segment.
+            let t = match length > 0.0 {
+                true => (((x - from.0) * dx + (y - from.1) * dy) / length).clamp(0.0, 1.0),
+                false => 0.0,
+            };
+            let (px, py) = (from.0 + t * dx - x, from.1 + t * dy - y);
+            radius + 0.5 - (px * px + py * py).sqrt()
+        });
+    }
+
+    pub fn disc(&mut self, center: (f32, f32), radius: f32, color: Rgba) {
+        if radius <= 0.0 {
+            return;
+        }
+        let center = self.sample(center);
+        let radius = radius * self.supersampling as f32;
+        let min = (center.0 - radius, center.1 - radius);
+        let max = (center.0 + radius, center.1 + radius);
+        self.cover(min, max, color, |x, y| {
+            let (dx, dy) = (x - center.0, y - center.1);
+            radius + 0.5 - (dx * dx + dy * dy).sqrt()
+        });
+    }
+
+    pub fn triangle(&mut self, a: (f32, f32), b: (f32, f32), c: (f32, f32), color: Rgba) {
+        let (a, b, c) = (self.sample(a), self.sample(b), self.sample(c));
+        let edge = |p: (f32, f32), q: (f32, f32), x: f32, y: f32| {
+            (q.0 - p.0) * (y - p.1) - (q.1 - p.1) * (x - p.0)
+        };
+        // Either winding.
+        let sign = edge(a, b, c.0, c.1).signum();
+        let min = (a.0.min(b.0).min(c.0), a.1.min(b.1).min(c.1));
+        let max = (a.0.max(b.0).max(c.0), a.1.max(b.1).max(c.1));
+        self.cover(min, max, color, |x, y| {
+            let inside = [(a, b), (b, c), (c, a)]
+                .iter()
+                .all(|&(p, q)| edge(p, q, x, y) * sign >= 0.0);
+            if inside {
+                1.0
+            } else {
+                0.0
+            }
+        });
+    }
+
+    /// The image as 8 bit RGBA, row by row.
+    pub fn pixels(&self) -> Vec<u8> {
+        let s = self.supersampling;
+        let row = self.width * s;
+        let mut pixels = Vec::with_capacity(self.width * self.height * 4);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                // Colours are averaged weighted by their alpha.
+                let mut sum = [0.0f32; 4];
+                for sy in 0..s {
+                    for sx in 0..s {
+                        let [r, g, b, a] = self.samples[(y * s + sy) * row + x * s + sx];
+                        let alpha = a as f32;
+                        sum[0] += r as f32 * alpha;
+                        sum[1] += g as f32 * alpha;
+                        sum[2] += b as f32 * alpha;
+                        sum[3] += alpha;
+                    }
+                }
+                let alpha = sum[3];
+                let channel = |c: f32| match alpha > 0.0 {
+                    true => (c / alpha).round() as u8,
+                    false => 0,
+                };
+                pixels.extend_from_slice(&[
+                    channel(sum[0]),
+                    channel(sum[1]),
+                    channel(sum[2]),
+                    (alpha / (s * s) as f32).round() as u8,
+                ]);
+            }
+        }
+        pixels
+    }
+
+    fn sample(&self, (x, y): (f32, f32)) -> (f32, f32) {
+        let s = self.supersampling as f32;
+        (x * s, y * s)
+    }
+
+    // Blends `color` into the samples between `min` and `max`, `coverage`
+    // of a sample center being clamped to `[0, 1]`.
+    fn cover(
+        &mut self,
+        min: (f32, f32),
+        max: (f32, f32),
+        color: Rgba,
+        coverage: impl Fn(f32, f32) -> f32,
+    ) {
+        let (columns, rows) = (
+            self.width * self.supersampling,
+            self.height * self.supersampling,
+        );
+        let range = |low: f32, high: f32, count: usize| {
+            let low = (low - 1.0).floor().max(0.0) as usize;
+            let high = ((high + 1.0).ceil().max(0.0) as usize).min(count);
+            low..high
+        };
+        for y in range(min.1, max.1, rows) {
+            for x in range(min.0, max.0, columns) {
+                let coverage = coverage(x as f32 + 0.5, y as f32 + 0.5).clamp(0.0, 1.0);
+                if coverage > 0.0 {
+                    blend(&mut self.samples[y * columns + x], color, coverage);
+                }
+            }
+        }
+    }
+}
+
+// Source over, in straight alpha.
+fn blend(sample: &mut [u8; 4], color: Rgba, coverage: f32) {
+    let source = color.a as f32 / 255.0 * coverage;
+    let destination = sample[3] as f32 / 255.0 * (1.0 - source);
+    let alpha = source + destination;
+    if alpha <= 0.0 {
+        return;
+    }
+    let mix = |s: u8, d: u8| ((s as f32 * source + d as f32 * destination) / alpha).round() as u8;
+    *sample = [
+        mix(color.r, sample[0]),
+        mix(color.g, sample[1]),
+        mix(color.b, sample[2]),
+        (alpha * 255.0).round() as u8,
+    ];
+}
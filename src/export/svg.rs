@@ -0,0 +1,244 @@
+use crate::graphics::turtle::{self, Point, Primitive};
+use crate::graphics::{Bounds, Config, Rgba};
+use crate::system::Module;
+use std::borrow::Borrow;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{self, BufWriter, Write as _};
+
+/// Renders a state to a standalone SVG document, without opening a window.
+///
+/// Lines are emitted as `<path>` elements, grouped in a `<g>` per branch
+/// depth, and the `viewBox` is fitted to the drawing. With a `size`, the
+/// drawing is scaled and centered to fill a document of that size.
+#[derive(Debug, Clone)]
+pub struct Svg {
+    pub padding: f32,
+    pub stroke: String,
+    pub background: Option<String>,
+    pub size: Option<(f32, f32)>,
+}
+
+impl Default for Svg {
+    fn default() -> Self {
+        Self {
+            padding: 10.0,
+            stroke: "#000000".to_string(),
+            background: None,
+            size: None,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Path {
+    width: f32,
+    color: Option<Rgba>,
+    points: Vec<(f32, f32)>,
+}
+
+#[derive(Debug, Default)]
+struct Drawing {
+    paths: BTreeMap<usize, Vec<Path>>,
+    dots: Vec<(f32, f32, f32, Option<Rgba>)>,
+    polygons: Vec<(Vec<(f32, f32)>, Rgba)>,
+    bounds: Option<Bounds>,
+}
+
+impl Drawing {
+    // SVG's y axis points down, the turtle's points up.
+    fn add(&mut self, primitive: Primitive) {
+        let flip = |p: &Point| (p.x, -p.y);
+        if let Some(bounds) = Bounds::of_primitive(&primitive) {
+            self.bounds = Some(match self.bounds {
+                Some(b) => b.union(bounds),
+                None => bounds,
+            });
+        }
+        match primitive {
+            Primitive::Segment {
+                from,
+                to,
+                width,
+                depth,
+                color,
+            } => self.line(depth, flip(&from), flip(&to), width, color),
+            Primitive::Dot { at, radius, color } => self.dot(flip(&at), radius, color),
+            Primitive::Polygon { vertices, color } => {
+                self.polygon(vertices.iter().map(flip).collect(), color)
+            }
+        }
+    }
+
+    fn line(
+        &mut self,
+        depth: usize,
+        from: (f32, f32),
+        to: (f32, f32),
+        width: f32,
+        color: Option<Rgba>,
+    ) {
+        let paths = self.paths.entry(depth).or_default();
+        match paths.last_mut() {
+            Some(path)
+                if path.width == width
+                    && path.color == color
+                    && path.points.last() == Some(&from) =>
+            {
+                path.points.push(to)
+            }
+            _ => paths.push(Path {
+                width,
+                color,
+                points: vec![from, to],
+            }),
+        }
+    }
+
+    fn dot(&mut self, at: (f32, f32), radius: f32, color: Option<Rgba>) {
+        self.dots.push((at.0, at.1, radius, color));
+    }
+
+    fn polygon(&mut self, vertices: Vec<(f32, f32)>, color: Rgba) {
+        self.polygons.push((vertices, color));
+    }
+}
+
+impl Svg {
+    /// Renders `modules` as they are walked: the modules aren't kept, but the
+    /// paths, dots and polygons of the document are, until it is written.
+    /// A [`Stream`](crate::system::Stream) may still hold a whole generation,
+    /// its docs say when.
+    pub fn render<M: Borrow<Module>>(
+        &self,
+        modules: impl IntoIterator<Item = M>,
+        config: &Config,
+    ) -> String {
+        let mut drawing = Drawing::default();
+        turtle::walk(modules, config, |primitive| drawing.add(primitive));
+        document(|out| self.write(&drawing, out))
+    }
+
+    pub fn render_primitives(&self, primitives: &[Primitive]) -> String {
+        let mut drawing = Drawing::default();
+        primitives.iter().for_each(|p| drawing.add(p.clone()));
+        document(|out| self.write(&drawing, out))
+    }
+
+    fn write(&self, drawing: &Drawing, out: &mut impl io::Write) -> io::Result<()> {
+        let (min, max) = match drawing.bounds {
+            Some(bounds) => ((bounds.min.x, -bounds.max.y), (bounds.max.x, -bounds.min.y)),
+            None => ((0.0, 0.0), (0.0, 0.0)),
+        };
+        let (x, y) = (round(min.0 - self.padding), round(min.1 - self.padding));
+        let width = round(max.0 - min.0 + 2.0 * self.padding);
+        let height = round(max.1 - min.1 + 2.0 * self.padding);
+        // The default `preserveAspectRatio` centers the `viewBox` and scales
+        // it uniformly to fit.
+        let (document_width, document_height) = self.size.unwrap_or((width, height));
+
+        writeln!(
+            out,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{} {} {} {}" width="{}" height="{}">"#,
+            x, y, width, height, document_width, document_height
+        )?;
+        if let Some(background) = &self.background {
+            writeln!(
+                out,
+                r#"  <rect x="{}" y="{}" width="{}" height="{}" fill="{}"/>"#,
+                x, y, width, height, background
+            )?;
+        }
+        // Fills go first, so that lines are drawn on top of them.
+        for (vertices, color) in &drawing.polygons {
+            let points: Vec<_> = vertices
+                .iter()
+                .map(|(px, py)| format!("{},{}", round(*px), round(*py)))
+                .collect();
+            writeln!(
+                out,
+                r#"  <polygon points="{}"{}/>"#,
+                points.join(" "),
+                paint("fill", *color)
+            )?;
+        }
+        for (depth, paths) in &drawing.paths {
+            writeln!(
+                out,
+                r#"  <g id="depth-{}" fill="none" stroke="{}" stroke-linecap="round" stroke-linejoin="round">"#,
+                depth, self.stroke
+            )?;
+            for path in paths {
+                let mut d = String::new();
+                for (i, (px, py)) in path.points.iter().enumerate() {
+                    let command = if i == 0 { 'M' } else { 'L' };
+                    let _ = write!(d, "{}{} {} ", command, round(*px), round(*py));
+                }
+                // Palette colours override the stroke of the group.
+                let stroke = path.color.map(|c| paint("stroke", c)).unwrap_or_default();
+                writeln!(
+                    out,
+                    r#"    <path d="{}" stroke-width="{}"{}/>"#,
+                    d.trim_end(),
+                    path.width,
+                    stroke
+                )?;
+            }
+            writeln!(out, "  </g>")?;
+        }
+        for (cx, cy, r, color) in &drawing.dots {
+            let fill = match color {
+                Some(color) => paint("fill", *color),
+                None => format!(r#" fill="{}""#, self.stroke),
+            };
+            writeln!(
+                out,
+                r#"  <circle cx="{}" cy="{}" r="{}"{}/>"#,
+                round(*cx),
+                round(*cy),
+                r,
+                fill
+            )?;
+        }
+        writeln!(out, "</svg>")
+    }
+
+    pub fn save<M: Borrow<Module>>(
+        &self,
+        modules: impl IntoIterator<Item = M>,
+        config: &Config,
+        path: &str,
+    ) -> io::Result<()> {
+        let mut drawing = Drawing::default();
+        turtle::walk(modules, config, |primitive| drawing.add(primitive));
+        let mut file = BufWriter::new(File::create(path)?);
+        self.write(&drawing, &mut file)?;
+        file.flush()
+    }
+}
+
+fn document(write: impl FnOnce(&mut Vec<u8>) -> io::Result<()>) -> String {
+    let mut bytes = Vec::new();
+    // Writing into memory can't fail, and only UTF-8 is written.
+    write(&mut bytes).unwrap();
+    String::from_utf8(bytes).unwrap()
+}
+
+// A colour as the `fill` or `stroke` attribute, and its opacity if any.
+fn paint(attribute: &str, color: Rgba) -> String {
+    match color.a {
+        255 => format!(r#" {}="{}""#, attribute, color.hex()),
+        a => format!(
+            r#" {}="{}" {}-opacity="{}""#,
+            attribute,
+            color.hex(),
+            attribute,
+            round(a as f32 / 255.0)
+        ),
+    }
+}
+
+fn round(value: f32) -> f32 {
+    (value * 1000.0).round() / 1000.0 + 0.0
+}
@@ -0,0 +1,106 @@
+use crate::graphics::turtle::{Point, Primitive};
+
+/// The box enclosing some primitives as drawn, line widths included.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bounds {
+    pub min: Point,
+    pub max: Point,
+}
+
+impl Bounds {
+    /// `None` when there is nothing to draw.
+    pub fn of(primitives: &[Primitive]) -> Option<Self> {
+        primitives
+            .iter()
+            .filter_map(Self::of_primitive)
+            .reduce(Self::union)
+    }
+
+    pub fn of_primitive(primitive: &Primitive) -> Option<Self> {
+        let around = |p: Point, radius: f32| {
+            let r = Point::xyz(radius, radius, radius);
+            Self {
+                min: p - r,
+                max: p + r,
+            }
+        };
+        match primitive {
+            Primitive::Segment {
+                from, to, width, ..
+            } => Some(around(*from, width / 2.0).union(around(*to, width / 2.0))),
+            Primitive::Dot { at, radius, .. } => Some(around(*at, *radius)),
+            Primitive::Polygon { vertices, .. } => {
+                vertices.iter().map(|v| around(*v, 0.0)).reduce(Self::union)
+            }
+        }
+    }
+
+    pub fn union(self, other: Self) -> Self {
+        let (a, b) = (self, other);
+        Self {
+            min: Point::xyz(
+                a.min.x.min(b.min.x),
+                a.min.y.min(b.min.y),
+                a.min.z.min(b.min.z),
+            ),
+            max: Point::xyz(
+                a.max.x.max(b.max.x),
+                a.max.y.max(b.max.y),
+                a.max.z.max(b.max.z),
+            ),
+        }
+    }
+
+    pub fn width(&self) -> f32 {
+        self.max.x - self.min.x
+    }
+
+    pub fn height(&self) -> f32 {
+        self.max.y - self.min.y
+    }
+
+    pub fn center(&self) -> Point {
+        (self.min + self.max) * 0.5
+    }
+}
+
+/// Maps turtle space onto a viewport whose y axis points down, a point
+/// `p` landing on `(x + p.x * scale, y - p.y * scale)`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    pub scale: f32,
+    pub x: f32,
+    pub y: f32,
+}
+
+impl Transform {
+    /// Places the turtle's origin at `(x, y)`, unscaled.
+    pub fn translate(x: f32, y: f32) -> Self {
+        Self { scale: 1.0, x, y }
+    }
+
+    /// Scales and centers `bounds` to fill a `width` by `height` viewport,
+    /// leaving `padding` on every side.
+    pub fn fit(bounds: &Bounds, width: f32, height: f32, padding: f32) -> Self {
+        let available = (
+            (width - 2.0 * padding).max(1.0),
+            (height - 2.0 * padding).max(1.0),
+        );
+        let scale = match (bounds.width() > 0.0, bounds.height() > 0.0) {
+            (true, true) => (available.0 / bounds.width()).min(available.1 / bounds.height()),
+            (true, false) => available.0 / bounds.width(),
+            (false, true) => available.1 / bounds.height(),
+            (false, false) => 1.0,
+        };
+        let center = bounds.center();
+        Self {
+            scale,
+            x: width / 2.0 - center.x * scale,
+            y: height / 2.0 + center.y * scale,
+        }
+    }
+
+    pub fn apply(&self, p: Point) -> (f32, f32) {
+        (self.x + p.x * self.scale, self.y - p.y * self.scale)
+    }
+}
@@ -1,10 +1,76 @@
-#[derive(Debug)]
+use crate::graphics::Rgba;
+use core::fmt;
+use std::str::FromStr;
+
+/// What picks the colour of lines, dots and polygons.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Coloring {
+    /// The palette, stepped through by `'`.
+    #[default]
+    Palette,
+    /// The gradient, from the trunk to the most nested branches.
+    Depth,
+    /// The gradient, by how far the turtle has moved from the start.
+    Distance,
+    /// The gradient, from the oldest modules to the newest.
+    Generation,
+}
+
+impl Coloring {
+    const ALL: [Self; 4] = [Self::Palette, Self::Depth, Self::Distance, Self::Generation];
+
+    /// The next mode, wrapping around, for cycling through them.
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|&c| c == self).unwrap();
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+}
+
+impl FromStr for Coloring {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|c| c.to_string() == s.to_lowercase())
+            .ok_or_else(|| {
+                format!(
+                    "Coloring: `{}` should be `palette`, `depth`, `distance` or `generation`.",
+                    s
+                )
+            })
+    }
+}
+
+impl fmt::Display for Coloring {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Palette => "palette",
+            Self::Depth => "depth",
+            Self::Distance => "distance",
+            Self::Generation => "generation",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct Config {
     pub line_length: i32,
     pub line_width_increment: f32,
     pub line_length_scale_factor: f64,
     pub turning_angle: f32,
     pub turning_angle_increment: f32,
+    /// 2 or 3, in 3D `&` pitches down instead of swapping `+` and `-`.
+    pub dimensions: u8,
+    /// The colour polygons drawn between `{` and `}` are filled with.
+    pub fill_color: Rgba,
+    /// The colours `'` steps through, wrapping around. Without any, lines
+    /// and dots are drawn in the colour of the renderer.
+    pub palette: Vec<Rgba>,
+    pub coloring: Coloring,
+    /// The stops of the gradient other colorings use, evenly spaced.
+    pub gradient: Vec<Rgba>,
 }
 
 impl Default for Config {
@@ -14,6 +80,10 @@ impl Default for Config {
 }
 
 impl Config {
+    pub const DEFAULT_FILL: Rgba = Rgba::new(76, 154, 42, 255);
+    pub const DEFAULT_GRADIENT: [Rgba; 2] =
+        [Rgba::new(139, 90, 43, 255), Rgba::new(154, 205, 50, 255)];
+
     pub fn new(
         line_length: i32,
         line_width_increment: f32,
@@ -27,6 +97,40 @@ impl Config {
             line_length_scale_factor,
             turning_angle: turning_angle.to_radians(),
             turning_angle_increment: turning_angle_increment.to_radians(),
+            dimensions: 2,
+            fill_color: Self::DEFAULT_FILL,
+            palette: Vec::new(),
+            coloring: Coloring::default(),
+            gradient: Self::DEFAULT_GRADIENT.to_vec(),
+        }
+    }
+
+    pub fn three_dimensional(mut self) -> Self {
+        self.dimensions = 3;
+        self
+    }
+
+    pub fn is_3d(&self) -> bool {
+        self.dimensions == 3
+    }
+
+    /// The colour at `index` of the palette, `None` if it's empty.
+    pub fn color(&self, index: usize) -> Option<Rgba> {
+        match self.palette.len() {
+            0 => None,
+            len => Some(self.palette[index % len]),
+        }
+    }
+
+    /// The colour at `t`, from 0 to 1, along the gradient. `None` if it has
+    /// no stops.
+    pub fn gradient_at(&self, t: f32) -> Option<Rgba> {
+        let last = self.gradient.len().checked_sub(1)?;
+        let position = t.clamp(0.0, 1.0) * last as f32;
+        let index = (position as usize).min(last.saturating_sub(1));
+        match self.gradient.get(index + 1) {
+            Some(&next) => Some(self.gradient[index].mix(next, position - index as f32)),
+            None => Some(self.gradient[index]),
         }
     }
 }
@@ -1,7 +1,22 @@
+pub mod bounds;
+pub mod color;
 pub mod config;
+pub mod orbit;
+pub mod pan_zoom;
+pub mod polygon;
+pub mod scrubber;
 pub mod state_drawer;
+pub mod turtle;
 pub mod visualizer;
+pub mod watcher;
 
-pub use config::Config;
+pub use bounds::{Bounds, Transform};
+pub use color::Rgba;
+pub use config::{Coloring, Config};
+pub use orbit::Orbit;
+pub use pan_zoom::PanZoom;
+pub use scrubber::Scrubber;
 use state_drawer::StateDrawer;
+pub use turtle::{Frame, Point, Primitive};
 pub use visualizer::Visualizer;
+pub use watcher::Watcher;
@@ -0,0 +1,72 @@
+use crate::graphics::turtle::{Point, Primitive};
+use crate::graphics::Bounds;
+use raylib::prelude::*;
+
+/// A camera circling the drawing: dragging with the left mouse button
+/// rotates it, the mouse wheel zooms.
+#[derive(Debug, Clone)]
+pub struct Orbit {
+    yaw: f32,
+    pitch: f32,
+    zoom: f32,
+    last_mouse: Option<Vector2>,
+}
+
+impl Default for Orbit {
+    fn default() -> Self {
+        Self {
+            yaw: 0.0,
+            pitch: 0.3,
+            zoom: 1.0,
+            last_mouse: None,
+        }
+    }
+}
+
+impl Orbit {
+    const SENSITIVITY: f32 = 0.01;
+    const ZOOM_STEP: f32 = 0.9;
+
+    pub fn update(&mut self, handle: &RaylibHandle) {
+        let mouse = handle.get_mouse_position();
+        if handle.is_mouse_button_down(MouseButton::MOUSE_LEFT_BUTTON) {
+            if let Some(last) = self.last_mouse {
+                self.yaw -= (mouse.x - last.x) * Self::SENSITIVITY;
+                self.pitch += (mouse.y - last.y) * Self::SENSITIVITY;
+                self.pitch = self.pitch.clamp(-1.5, 1.5);
+            }
+            self.last_mouse = Some(mouse);
+        } else {
+            self.last_mouse = None;
+        }
+
+        let wheel = handle.get_mouse_wheel_move();
+        if wheel != 0.0 {
+            self.zoom *= Self::ZOOM_STEP.powf(wheel);
+        }
+    }
+
+    /// A camera looking at the center of `primitives`, far enough away to
+    /// see all of them at the default zoom.
+    pub fn camera(&self, primitives: &[Primitive]) -> Camera3D {
+        let (center, radius) = match Bounds::of(primitives) {
+            Some(bounds) => (bounds.center(), (bounds.max - bounds.min).length() / 2.0),
+            None => (Point::default(), 0.0),
+        };
+        let radius = radius.max(1.0);
+        let distance = 2.0 * radius * self.zoom;
+
+        let direction = Point::xyz(
+            self.yaw.sin() * self.pitch.cos(),
+            self.pitch.sin(),
+            self.yaw.cos() * self.pitch.cos(),
+        );
+        let position = center + direction * distance;
+        Camera3D::perspective(
+            Vector3::new(position.x, position.y, position.z),
+            Vector3::new(center.x, center.y, center.z),
+            Vector3::new(0.0, 1.0, 0.0),
+            60.0,
+        )
+    }
+}
@@ -0,0 +1,58 @@
+use raylib::prelude::*;
+
+/// A 2D camera over the fitted drawing: dragging with the left mouse button
+/// pans, the mouse wheel zooms around the cursor.
+#[derive(Debug, Clone)]
+pub struct PanZoom {
+    camera: Camera2D,
+    last_mouse: Option<Vector2>,
+}
+
+impl Default for PanZoom {
+    fn default() -> Self {
+        Self {
+            camera: Camera2D {
+                offset: Vector2::zero(),
+                target: Vector2::zero(),
+                rotation: 0.0,
+                zoom: 1.0,
+            },
+            last_mouse: None,
+        }
+    }
+}
+
+impl PanZoom {
+    const ZOOM_STEP: f32 = 1.1;
+    const MIN_ZOOM: f32 = 0.01;
+    const MAX_ZOOM: f32 = 1000.0;
+
+    pub fn camera(&self) -> Camera2D {
+        self.camera
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    pub fn update(&mut self, handle: &RaylibHandle) {
+        let mouse = handle.get_mouse_position();
+        if handle.is_mouse_button_down(MouseButton::MOUSE_LEFT_BUTTON) {
+            if let Some(last) = self.last_mouse {
+                self.camera.target -= (mouse - last) / self.camera.zoom;
+            }
+            self.last_mouse = Some(mouse);
+        } else {
+            self.last_mouse = None;
+        }
+
+        // Zooming keeps the world point under the cursor in place.
+        let wheel = handle.get_mouse_wheel_move();
+        if wheel != 0.0 {
+            self.camera.target = handle.get_screen_to_world2D(mouse, self.camera);
+            self.camera.offset = mouse;
+            self.camera.zoom = (self.camera.zoom * Self::ZOOM_STEP.powf(wheel))
+                .clamp(Self::MIN_ZOOM, Self::MAX_ZOOM);
+        }
+    }
+}
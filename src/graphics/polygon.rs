@@ -0,0 +1,94 @@
+// Triangulation of the polygons drawn between `{` and `}`.
+//
+// Polygons are triangulated by ear clipping, after projecting them onto the
+// coordinate plane they are most parallel to, so leaves drawn in 3D work
+// the same as flat ones. Self-intersecting outlines have no ears left at
+// some point, the rest of them is then filled as a fan.
+
+use crate::graphics::turtle::Point;
+
+/// Splits `vertices` into triangles, as indices into `vertices`.
+pub fn triangulate(vertices: &[Point]) -> Vec<[usize; 3]> {
+    let points = project(vertices);
+    let mut remaining: Vec<usize> = (0..points.len()).collect();
+    if signed_area(&points) < 0.0 {
+        remaining.reverse();
+    }
+
+    let mut triangles = Vec::new();
+    while remaining.len() > 3 {
+        let n = remaining.len();
+        let ear = (0..n).find(|&i| {
+            let (a, b, c) = (
+                remaining[(i + n - 1) % n],
+                remaining[i],
+                remaining[(i + 1) % n],
+            );
+            cross(points[a], points[b], points[c]) > 0.0
+                && !remaining
+                    .iter()
+                    .filter(|&&p| p != a && p != b && p != c)
+                    .any(|&p| contains([points[a], points[b], points[c]], points[p]))
+        });
+        let Some(i) = ear else {
+            break;
+        };
+        triangles.push([
+            remaining[(i + n - 1) % n],
+            remaining[i],
+            remaining[(i + 1) % n],
+        ]);
+        remaining.remove(i);
+    }
+
+    if remaining.len() >= 3 {
+        triangles.extend(
+            (1..remaining.len() - 1).map(|i| [remaining[0], remaining[i], remaining[i + 1]]),
+        );
+    }
+    triangles
+}
+
+// Drops the axis along which the polygon's normal (Newell's method) is
+// largest.
+fn project(vertices: &[Point]) -> Vec<(f32, f32)> {
+    let normal =
+        vertices
+            .iter()
+            .zip(vertices.iter().cycle().skip(1))
+            .fold(Point::default(), |n, (a, b)| {
+                n + Point::xyz(
+                    (a.y - b.y) * (a.z + b.z),
+                    (a.z - b.z) * (a.x + b.x),
+                    (a.x - b.x) * (a.y + b.y),
+                )
+            });
+    let (x, y, z) = (normal.x.abs(), normal.y.abs(), normal.z.abs());
+    let axes = |p: &Point| {
+        if z >= x && z >= y {
+            (p.x, p.y)
+        } else if y >= x {
+            (p.z, p.x)
+        } else {
+            (p.y, p.z)
+        }
+    };
+    vertices.iter().map(axes).collect()
+}
+
+fn signed_area(points: &[(f32, f32)]) -> f32 {
+    points
+        .iter()
+        .zip(points.iter().cycle().skip(1))
+        .map(|(a, b)| a.0 * b.1 - b.0 * a.1)
+        .sum::<f32>()
+        / 2.0
+}
+
+fn cross(a: (f32, f32), b: (f32, f32), c: (f32, f32)) -> f32 {
+    (b.0 - a.0) * (c.1 - a.1) - (b.1 - a.1) * (c.0 - a.0)
+}
+
+fn contains([a, b, c]: [(f32, f32); 3], p: (f32, f32)) -> bool {
+    cross(a, b, p) >= 0.0 && cross(b, c, p) >= 0.0 && cross(c, a, p) >= 0.0
+}
@@ -0,0 +1,69 @@
+use raylib::prelude::*;
+
+/// A bar spanning the generations seen so far: pressing or dragging on it
+/// picks a generation.
+#[derive(Debug, Clone, Default)]
+pub struct Scrubber {
+    last: usize,
+    dragging: bool,
+}
+
+impl Scrubber {
+    const MARGIN: f32 = 16.0;
+    // Ticks closer together than this, in pixels, are left out.
+    const MIN_TICK_SPACING: f32 = 4.0;
+
+    /// Grows the bar to include `generation`.
+    pub fn extend(&mut self, generation: usize) {
+        self.last = self.last.max(generation);
+    }
+
+    pub fn is_dragging(&self) -> bool {
+        self.dragging
+    }
+
+    /// The generation under the mouse while dragging on `area`.
+    pub fn update(&mut self, handle: &RaylibHandle, area: Rectangle) -> Option<usize> {
+        let mouse = handle.get_mouse_position();
+        if handle.is_mouse_button_pressed(MouseButton::MOUSE_LEFT_BUTTON) {
+            self.dragging = area.check_collision_point_rec(mouse);
+        } else if !handle.is_mouse_button_down(MouseButton::MOUSE_LEFT_BUTTON) {
+            self.dragging = false;
+        }
+
+        self.dragging.then(|| {
+            let (left, width) = Self::track(area);
+            let t = ((mouse.x - left) / width).clamp(0.0, 1.0);
+            (t * self.last as f32).round() as usize
+        })
+    }
+
+    pub fn draw(&self, d: &mut RaylibDrawHandle, area: Rectangle, current: usize, color: Color) {
+        let (left, width) = Self::track(area);
+        let y = area.y + area.height / 2.0;
+        let x = |generation: usize| left + width * generation as f32 / self.last.max(1) as f32;
+
+        d.draw_line_ex(
+            Vector2::new(left, y),
+            Vector2::new(left + width, y),
+            1.0,
+            color,
+        );
+        if width / self.last.max(1) as f32 >= Self::MIN_TICK_SPACING {
+            for generation in 0..=self.last {
+                d.draw_line_ex(
+                    Vector2::new(x(generation), y - area.height / 6.0),
+                    Vector2::new(x(generation), y + area.height / 6.0),
+                    1.0,
+                    color,
+                );
+            }
+        }
+        d.draw_circle_v(Vector2::new(x(current), y), area.height / 4.0, color);
+    }
+
+    fn track(area: Rectangle) -> (f32, f32) {
+        let left = area.x + Self::MARGIN;
+        (left, (area.width - 2.0 * Self::MARGIN).max(1.0))
+    }
+}
@@ -1,71 +1,154 @@
-use crate::graphics::Config;
-use crate::system::{State, Symbol};
+use crate::graphics::bounds::Transform;
+use crate::graphics::pan_zoom::PanZoom;
+use crate::graphics::polygon;
+use crate::graphics::turtle::{self, Point, Primitive};
+use crate::graphics::{Config, Rgba};
+use crate::system::State;
 use raylib::prelude::*;
 
+/// `color` is used for lines and dots without a colour of their own, when
+/// the system has no palette.
 pub trait StateDrawer {
-    fn draw_state(&mut self, state: &State, x: i32, y: i32, config: &mut Config, color: Color);
+    fn draw_state(&mut self, state: &State, x: i32, y: i32, config: &Config, color: Color);
+
+    /// Draws through `camera`, skipping what falls outside of its view.
+    /// Line widths and dot radii stay in pixels whatever the scale.
+    fn draw_primitives(
+        &mut self,
+        primitives: &[Primitive],
+        transform: Transform,
+        camera: Camera2D,
+        color: Color,
+    );
+
+    fn draw_primitives_3d(&mut self, primitives: &[Primitive], camera: Camera3D, color: Color);
 }
 
 impl StateDrawer for RaylibDrawHandle<'_> {
-    fn draw_state(&mut self, state: &State, x: i32, y: i32, config: &mut Config, color: Color) {
-        let mut pos = Vector2::new(x as f32, y as f32);
-        let mut angle = std::f32::consts::PI / 2.0;
+    fn draw_state(&mut self, state: &State, x: i32, y: i32, config: &Config, color: Color) {
+        let transform = Transform::translate(x as f32, y as f32);
+        let camera = PanZoom::default().camera();
+        self.draw_primitives(&turtle::interpret(state, config), transform, camera, color);
+    }
 
-        let mut stack = Vec::<(Vector2, f32)>::new();
-        let mut thickness = 1.0;
+    fn draw_primitives(
+        &mut self,
+        primitives: &[Primitive],
+        transform: Transform,
+        camera: Camera2D,
+        color: Color,
+    ) {
+        let screen = |p: &Point| {
+            let (x, y) = transform.apply(*p);
+            Vector2::new(x, y)
+        };
+        // The part of the world the camera sees.
+        let size = Vector2::new(
+            self.get_screen_width() as f32,
+            self.get_screen_height() as f32,
+        );
+        let min = camera.target - camera.offset / camera.zoom;
+        let max = min + size / camera.zoom;
+        let visible = |points: &[Vector2], margin: f32| overlaps(points, margin, min, max);
+        // The camera scales everything drawn through it, widths included.
+        let pixels = |size: f32| size / camera.zoom;
+        let mut d = self.begin_mode2D(camera);
 
-        for symbol in state {
-            match symbol {
-                Symbol::Var(_) => {}
-                // TODO: Draw only inside of the screen bounds
-                Symbol::Draw => {
-                    let temp = Vector2::new(
-                        pos.x - config.line_length as f32 * angle.cos(),
-                        pos.y - config.line_length as f32 * angle.sin(),
-                    );
-                    if 0.0 <= temp.x
-                        && temp.x <= self.get_screen_width() as f32
-                        && 0.0 <= temp.y
-                        && temp.y <= self.get_screen_height() as f32
-                    {
-                        self.draw_line_ex(pos, temp, thickness, color);
+        for primitive in primitives {
+            match primitive {
+                Primitive::Segment {
+                    from,
+                    to,
+                    width: thickness,
+                    color: own,
+                    ..
+                } => {
+                    let (from, to, thickness) = (screen(from), screen(to), pixels(*thickness));
+                    if visible(&[from, to], thickness) {
+                        d.draw_line_ex(from, to, thickness, own.as_ref().map_or(color, fill));
                     }
-                    pos = temp;
                 }
-                Symbol::Move => {
-                    pos = Vector2::new(
-                        pos.x - config.line_length as f32 * angle.cos(),
-                        pos.y - config.line_length as f32 * angle.sin(),
-                    )
-                }
-                Symbol::Left => angle -= config.turning_angle,
-                Symbol::Right => angle += config.turning_angle,
-                Symbol::Reverse => angle += std::f32::consts::PI,
-                Symbol::Push => stack.push((pos, angle)),
-                Symbol::Pop => {
-                    if !stack.is_empty() {
-                        (pos, angle) = stack.pop().unwrap();
+                Primitive::Dot {
+                    at,
+                    radius,
+                    color: own,
+                } => {
+                    let (at, radius) = (screen(at), pixels(*radius));
+                    if visible(&[at], radius) {
+                        d.draw_circle_v(at, radius, own.as_ref().map_or(color, fill));
                     }
                 }
-                Symbol::IncLine => thickness += config.line_width_increment,
-                Symbol::DecLine => thickness -= config.line_width_increment,
-                Symbol::Dot => {
-                    self.draw_circle(pos.x as i32, pos.y as i32, config.line_length as f32, color)
-                }
-                Symbol::OpenPolygon => todo!(),
-                Symbol::ClosePolygon => todo!(),
-                Symbol::MulLine => {
-                    config.line_length =
-                        (config.line_length as f64 * config.line_length_scale_factor) as i32
+                Primitive::Polygon { vertices, color } => {
+                    let points: Vec<_> = vertices.iter().map(screen).collect();
+                    if !visible(&points, 0.0) {
+                        continue;
+                    }
+                    for [a, b, c] in polygon::triangulate(vertices) {
+                        let (a, b, c) = (points[a], points[b], points[c]);
+                        // Raylib only fills counterclockwise triangles, on a
+                        // screen whose y axis points down.
+                        match (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x) < 0.0 {
+                            true => d.draw_triangle(a, b, c, fill(color)),
+                            false => d.draw_triangle(a, c, b, fill(color)),
+                        }
+                    }
                 }
-                Symbol::DivLine => {
-                    config.line_length =
-                        (config.line_length as f64 * config.line_length_scale_factor) as i32
+            }
+        }
+    }
+
+    // Raylib's world space is y-up and right-handed, like turtle space.
+    fn draw_primitives_3d(&mut self, primitives: &[Primitive], camera: Camera3D, color: Color) {
+        let world = |p: &Point| Vector3::new(p.x, p.y, p.z);
+        let mut d = self.begin_mode3D(camera);
+
+        for primitive in primitives {
+            match primitive {
+                Primitive::Segment {
+                    from,
+                    to,
+                    color: own,
+                    ..
+                } => d.draw_line_3D(world(from), world(to), own.as_ref().map_or(color, fill)),
+                Primitive::Dot {
+                    at,
+                    radius,
+                    color: own,
+                } => d.draw_sphere(world(at), *radius, own.as_ref().map_or(color, fill)),
+                // Both windings, so that leaves are visible from either side.
+                Primitive::Polygon { vertices, color } => {
+                    for [a, b, c] in polygon::triangulate(vertices) {
+                        let (a, b, c) = (
+                            world(&vertices[a]),
+                            world(&vertices[b]),
+                            world(&vertices[c]),
+                        );
+                        d.draw_triangle3D(a, b, c, fill(color));
+                        d.draw_triangle3D(a, c, b, fill(color));
+                    }
                 }
-                Symbol::SwapOperations => todo!(),
-                Symbol::IncAngle => angle += config.turning_angle_increment,
-                Symbol::DecAngle => angle -= config.turning_angle_increment,
             }
         }
     }
 }
+
+fn fill(color: &Rgba) -> Color {
+    Color::new(color.r, color.g, color.b, color.a)
+}
+
+// Whether the bounding box of `points`, grown by `margin`, overlaps the
+// rectangle from `min` to `max`.
+fn overlaps(points: &[Vector2], margin: f32, min: Vector2, max: Vector2) -> bool {
+    let (mut low, mut high) = (
+        Vector2::new(f32::MAX, f32::MAX),
+        Vector2::new(f32::MIN, f32::MIN),
+    );
+    for p in points {
+        low = Vector2::new(low.x.min(p.x), low.y.min(p.y));
+        high = Vector2::new(high.x.max(p.x), high.y.max(p.y));
+    }
+    low.x - margin <= max.x
+        && high.x + margin >= min.x
+        && low.y - margin <= max.y
+        && high.y + margin >= min.y
+}
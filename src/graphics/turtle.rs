@@ -0,0 +1,369 @@
+// Backend-agnostic turtle interpretation.
+//
+// A state is walked once and turned into a list of primitives in turtle
+// space: the turtle starts at the origin heading towards +y, and `+` turns
+// it counterclockwise. Renderers and exporters only map these primitives to
+// their own coordinates, so all of them agree on the geometry.
+//
+// The turtle is oriented by its heading H, left L and up U vectors (with
+// H × L = U), starting with U towards the viewer at +z. 2D systems only
+// ever turn around U, so they stay in the z = 0 plane and 2D backends can
+// simply drop z.
+
+use crate::graphics::{Coloring, Config, Rgba};
+use crate::system::{Module, Symbol};
+use std::borrow::Borrow;
+use std::ops::{Add, Mul, Neg, Sub};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Point {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+impl Point {
+    pub fn new(x: f32, y: f32) -> Self {
+        Self::xyz(x, y, 0.0)
+    }
+
+    pub fn xyz(x: f32, y: f32, z: f32) -> Self {
+        Self { x, y, z }
+    }
+
+    pub fn dot(self, other: Self) -> f32 {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    pub fn cross(self, other: Self) -> Self {
+        Self::xyz(
+            self.y * other.z - self.z * other.y,
+            self.z * other.x - self.x * other.z,
+            self.x * other.y - self.y * other.x,
+        )
+    }
+
+    pub fn length(self) -> f32 {
+        self.dot(self).sqrt()
+    }
+
+    pub fn normalized(self) -> Self {
+        self * (1.0 / self.length())
+    }
+}
+
+impl Add for Point {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self::xyz(self.x + other.x, self.y + other.y, self.z + other.z)
+    }
+}
+
+impl Sub for Point {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self::xyz(self.x - other.x, self.y - other.y, self.z - other.z)
+    }
+}
+
+impl Mul<f32> for Point {
+    type Output = Self;
+
+    fn mul(self, scale: f32) -> Self {
+        Self::xyz(self.x * scale, self.y * scale, self.z * scale)
+    }
+}
+
+impl Neg for Point {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        self * -1.0
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Primitive {
+    /// A line drawn by `F`, `depth` being its bracket nesting depth.
+    /// Without a palette or a gradient, `color` is `None` and renderers use
+    /// their own.
+    Segment {
+        from: Point,
+        to: Point,
+        width: f32,
+        depth: usize,
+        color: Option<Rgba>,
+    },
+    /// A dot drawn by `@`.
+    Dot {
+        at: Point,
+        radius: f32,
+        color: Option<Rgba>,
+    },
+    /// The vertices recorded between `{` and `}`, to be filled with the
+    /// colour of the palette or gradient, or else the fill colour.
+    Polygon { vertices: Vec<Point>, color: Rgba },
+}
+
+/// The orientation of the turtle.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Frame {
+    pub heading: Point,
+    pub left: Point,
+    pub up: Point,
+}
+
+impl Default for Frame {
+    fn default() -> Self {
+        Self {
+            heading: Point::xyz(0.0, 1.0, 0.0),
+            left: Point::xyz(-1.0, 0.0, 0.0),
+            up: Point::xyz(0.0, 0.0, 1.0),
+        }
+    }
+}
+
+// Turns `a` towards `b` by `angle` in the plane they span.
+fn rotate(a: Point, b: Point, angle: f32) -> (Point, Point) {
+    let (sin, cos) = angle.sin_cos();
+    (a * cos + b * sin, b * cos - a * sin)
+}
+
+impl Frame {
+    /// Turns left around U, or right for a negative angle.
+    pub fn turn(&mut self, angle: f32) {
+        (self.heading, self.left) = rotate(self.heading, self.left, angle);
+    }
+
+    /// Pitches up around L, or down for a negative angle.
+    pub fn pitch(&mut self, angle: f32) {
+        (self.heading, self.up) = rotate(self.heading, self.up, angle);
+    }
+
+    /// Rolls left around H, or right for a negative angle.
+    pub fn roll(&mut self, angle: f32) {
+        (self.left, self.up) = rotate(self.left, self.up, angle);
+    }
+
+    /// Rolls around H until L is horizontal, `L = V × H / |V × H|` with the
+    /// vertical V being +y. A vertical heading is left as is.
+    pub fn level(&mut self) {
+        let left = Point::xyz(0.0, 1.0, 0.0).cross(self.heading);
+        if left.length() > 1e-6 {
+            self.left = left.normalized();
+            self.up = self.heading.cross(self.left);
+        }
+    }
+}
+
+// Everything `[` saves and `]` restores. The `Config` is only read, so
+// nothing done inside a branch outlives it.
+#[derive(Debug, Clone)]
+struct Turtle {
+    position: Point,
+    frame: Frame,
+    thickness: f32,
+    line_length: f32,
+    turning_angle: f32,
+    // Into the palette of the `Config`.
+    color: usize,
+    // The length of the path from the start, moves included.
+    distance: f32,
+    // Set by `&` in 2D, `+` then turns right and `-` left.
+    swapped: bool,
+}
+
+/// Walks `state` and returns the primitives it draws.
+pub fn interpret<M: Borrow<Module>>(
+    state: impl IntoIterator<Item = M>,
+    config: &Config,
+) -> Vec<Primitive> {
+    let mut primitives = Vec::new();
+    walk(state, config, |primitive| primitives.push(primitive));
+    primitives
+}
+
+/// Walks `modules`, handing each primitive drawn to `draw` as it goes, so
+/// that a [`Stream`](crate::system::Stream) can be drawn without keeping
+/// either the modules or the primitives.
+///
+/// A gradient is spread over the range of depths, distances or births of
+/// the whole drawing, which is only known at the end. The primitives are
+/// then kept until the walk is over.
+pub fn walk<M: Borrow<Module>>(
+    modules: impl IntoIterator<Item = M>,
+    config: &Config,
+    mut draw: impl FnMut(Primitive),
+) {
+    if config.coloring == Coloring::Palette || config.gradient.is_empty() {
+        return trace(modules, config, |primitive, _| draw(primitive));
+    }
+
+    let mut traced = Vec::new();
+    trace(modules, config, |primitive, value| {
+        traced.push((primitive, value))
+    });
+    let (min, max) = traced
+        .iter()
+        .fold((f32::MAX, f32::MIN), |(min, max), &(_, value)| {
+            (min.min(value), max.max(value))
+        });
+    for (mut primitive, value) in traced {
+        let t = match max > min {
+            true => (value - min) / (max - min),
+            false => 0.0,
+        };
+        let gradient = config.gradient_at(t);
+        match &mut primitive {
+            Primitive::Segment { color, .. } | Primitive::Dot { color, .. } => *color = gradient,
+            Primitive::Polygon { color, .. } => *color = gradient.unwrap_or(*color),
+        }
+        draw(primitive);
+    }
+}
+
+// Walks `modules`, handing each primitive to `draw` with the value the
+// coloring of the `Config` places it by on the gradient.
+fn trace<M: Borrow<Module>>(
+    modules: impl IntoIterator<Item = M>,
+    config: &Config,
+    mut draw: impl FnMut(Primitive, f32),
+) {
+    let mut turtle = Turtle {
+        position: Point::default(),
+        frame: Frame::default(),
+        thickness: 1.0,
+        line_length: config.line_length as f32,
+        turning_angle: config.turning_angle,
+        color: 0,
+        distance: 0.0,
+        swapped: false,
+    };
+    let mut stack = Vec::<Turtle>::new();
+    // Polygons may be nested, a `{` in a branch of an open polygon starts a
+    // separate one, and vertices go to the innermost.
+    let mut polygons = Vec::<Vec<Point>>::new();
+
+    for module in modules {
+        let module = module.borrow();
+        // The first parameter of a module overrides the `Config` value the
+        // command would otherwise use, e.g. `F(10)` or `+(30)`.
+        let length = module.param(0).unwrap_or(turtle.line_length);
+        let turn = module
+            .param(0)
+            .map_or(turtle.turning_angle, f32::to_radians);
+        let left = match turtle.swapped {
+            true => -turn,
+            false => turn,
+        };
+        let width = module.param(0).unwrap_or(config.line_width_increment);
+        let scale = module
+            .param(0)
+            .unwrap_or(config.line_length_scale_factor as f32);
+        let increment = module
+            .param(0)
+            .map_or(config.turning_angle_increment, f32::to_radians);
+        let value = match config.coloring {
+            Coloring::Palette => 0.0,
+            Coloring::Depth => stack.len() as f32,
+            Coloring::Distance => turtle.distance,
+            Coloring::Generation => module.birth as f32,
+        };
+
+        match module.symbol {
+            Symbol::Var(_) => {}
+            Symbol::Draw => {
+                let to = turtle.position + turtle.frame.heading * length;
+                draw(
+                    Primitive::Segment {
+                        from: turtle.position,
+                        to,
+                        width: turtle.thickness,
+                        depth: stack.len(),
+                        color: config.color(turtle.color),
+                    },
+                    value,
+                );
+                turtle.position = to;
+                turtle.distance += length.abs();
+            }
+            Symbol::Move => {
+                turtle.position = turtle.position + turtle.frame.heading * length;
+                turtle.distance += length.abs();
+            }
+            Symbol::Left => turtle.frame.turn(left),
+            Symbol::Right => turtle.frame.turn(-left),
+            Symbol::Reverse => turtle.frame.turn(std::f32::consts::PI),
+            Symbol::Push => stack.push(turtle.clone()),
+            Symbol::Pop => {
+                if let Some(saved) = stack.pop() {
+                    turtle = saved;
+                }
+            }
+            Symbol::IncLine => turtle.thickness += width,
+            Symbol::DecLine => turtle.thickness = (turtle.thickness - width).max(0.0),
+            Symbol::Dot => draw(
+                Primitive::Dot {
+                    at: turtle.position,
+                    radius: turtle.thickness,
+                    color: config.color(turtle.color),
+                },
+                value,
+            ),
+            Symbol::OpenPolygon => polygons.push(vec![turtle.position]),
+            Symbol::ClosePolygon => {
+                if let Some(mut vertices) = polygons.pop() {
+                    if vertices.len() > 1 && coincide(vertices[0], vertices[vertices.len() - 1]) {
+                        vertices.pop();
+                    }
+                    if vertices.len() >= 3 {
+                        draw(
+                            Primitive::Polygon {
+                                vertices,
+                                color: config.color(turtle.color).unwrap_or(config.fill_color),
+                            },
+                            value,
+                        );
+                    }
+                }
+            }
+            Symbol::Vertex => {}
+            Symbol::MulLine => turtle.line_length *= scale,
+            Symbol::DivLine => turtle.line_length /= scale,
+            Symbol::SwapOperations if config.is_3d() => turtle.frame.pitch(-turn),
+            Symbol::SwapOperations => turtle.swapped = !turtle.swapped,
+            Symbol::IncAngle => turtle.turning_angle += increment,
+            Symbol::DecAngle => turtle.turning_angle -= increment,
+            Symbol::PitchUp if config.is_3d() => turtle.frame.pitch(turn),
+            Symbol::RollLeft if config.is_3d() => turtle.frame.roll(turn),
+            Symbol::RollRight if config.is_3d() => turtle.frame.roll(-turn),
+            Symbol::Level if config.is_3d() => turtle.frame.level(),
+            // Variables in 2D, as they were before 3D systems.
+            Symbol::PitchUp | Symbol::RollLeft | Symbol::RollRight | Symbol::Level => {}
+            Symbol::NextColor => {
+                turtle.color = match module.param(0) {
+                    Some(index) => index.max(0.0) as usize,
+                    None => turtle.color + 1,
+                }
+            }
+        }
+
+        // Moving inside a polygon records a vertex just like `.` does.
+        if let (Some(vertices), Symbol::Draw | Symbol::Move | Symbol::Vertex) =
+            (polygons.last_mut(), &module.symbol)
+        {
+            if !vertices
+                .last()
+                .is_some_and(|&last| coincide(last, turtle.position))
+            {
+                vertices.push(turtle.position);
+            }
+        }
+    }
+}
+
+fn coincide(a: Point, b: Point) -> bool {
+    (a - b).length() < 1e-4
+}
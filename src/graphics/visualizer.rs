@@ -1,77 +1,199 @@
-use crate::graphics::Config;
+use crate::graphics::turtle;
+use crate::graphics::Orbit;
+use crate::graphics::PanZoom;
+use crate::graphics::Scrubber;
 use crate::graphics::StateDrawer;
-use crate::system::{State, System, SystemIterator};
+use crate::graphics::Watcher;
+use crate::graphics::{Bounds, Transform};
+use crate::graphics::{Coloring, Config};
+use crate::parser::{self, error::ParseError};
+use crate::system::{History, State, System};
 use raylib::prelude::*;
 use std::sync::mpsc::Sender;
 use std::sync::{mpsc, Arc, Mutex};
 use std::thread;
 use std::time::Instant;
 
+// What the history thread is asked to do.
+enum Message {
+    Show(usize),
+    // Starts a new history, for a reloaded system.
+    Replace(System),
+    Stop,
+}
+
+// The generation on screen and how long it took to get.
+struct Shown {
+    generation: usize,
+    state: State,
+    duration: f64,
+}
+
 pub struct Visualizer {
     width: i32,
     height: i32,
     name: &'static str,
-    system: Option<System>,
+    system: System,
     config: Config,
+    orbit: Option<Orbit>,
+    pan_zoom: PanZoom,
+    scrubber: Scrubber,
+    fit: bool,
+    watcher: Option<Watcher>,
+    seed: Option<u64>,
+    error: Option<String>,
+    // Digits typed so far of a generation to jump to.
+    input: String,
+    // Picked with `G`, it outlasts reloads.
+    coloring: Option<Coloring>,
 }
 
 impl Visualizer {
     const FONT_PATH: &str = "assets/Iosevka-Light.ttf";
     const FONT_SCALE: i32 = 30;
     const PADDING: i32 = 4;
+    const FIT_PADDING: f32 = 20.0;
+    const HISTORY_BUDGET: usize = 256 << 20;
+    const DIGITS: [KeyboardKey; 10] = [
+        KeyboardKey::KEY_ZERO,
+        KeyboardKey::KEY_ONE,
+        KeyboardKey::KEY_TWO,
+        KeyboardKey::KEY_THREE,
+        KeyboardKey::KEY_FOUR,
+        KeyboardKey::KEY_FIVE,
+        KeyboardKey::KEY_SIX,
+        KeyboardKey::KEY_SEVEN,
+        KeyboardKey::KEY_EIGHT,
+        KeyboardKey::KEY_NINE,
+    ];
 
-    pub fn new(name: &'static str, (width, height): (i32, i32), config: Config) -> Self {
+    pub fn new(
+        name: &'static str,
+        (width, height): (i32, i32),
+        system: System,
+        config: Config,
+    ) -> Self {
+        let orbit = config.is_3d().then(Orbit::default);
+        let system = system.with_births(config.coloring == Coloring::Generation);
         Self {
             width,
             height,
             name,
-            system: None,
+            system,
             config,
+            orbit,
+            pan_zoom: PanZoom::default(),
+            scrubber: Scrubber::default(),
+            fit: true,
+            watcher: None,
+            seed: None,
+            error: None,
+            input: String::new(),
+            coloring: None,
         }
     }
 
+    /// Reloads the system whenever the file at `path` changes, keeping the
+    /// current generation. `seed`, if any, overrides the seed of the file.
+    pub fn watch(mut self, path: &str, seed: Option<u64>) -> Self {
+        self.watcher = Some(Watcher::new(path));
+        self.seed = seed;
+        self
+    }
+
     pub fn run(&mut self) {
         let (mut handle, thread) = self.init();
         let font = Self::load_font(&mut handle, &thread);
         handle.set_target_fps(20);
 
-        let iterator = Arc::new(Mutex::new(self.system.clone().into_iter()));
-        let duration = Arc::new(Mutex::new(0.0));
-        let sender = self.create_iterator_channel(&iterator, &duration);
+        let shown = Arc::new(Mutex::new(Shown {
+            generation: 0,
+            state: self.system.start().clone(),
+            duration: 0.0,
+        }));
+        let sender = self.create_history_channel(&shown);
 
-        let mut iteration = 1;
+        let mut generation = 0;
 
         while !handle.window_should_close() {
             if handle.is_window_resized() {
                 self.resize(&handle);
             }
+            let requested = generation;
+            if let Some(scrubbed) = self.scrubber.update(&handle, self.scrubber_area()) {
+                generation = scrubbed;
+            }
+            if !self.scrubber.is_dragging() {
+                match &mut self.orbit {
+                    Some(orbit) => orbit.update(&handle),
+                    None => self.pan_zoom.update(&handle),
+                }
+            }
+            if self.watcher.as_mut().is_some_and(Watcher::changed) {
+                match self.reload() {
+                    Ok(()) => {
+                        sender.send(Message::Replace(self.system.clone())).unwrap();
+                        sender.send(Message::Show(generation)).unwrap();
+                    }
+                    Err(e) => self.error = Some(e.to_string()),
+                }
+            }
 
             {
                 let mut d = handle.begin_drawing(&thread);
-                if self.system.is_some() {
-                    let iter = iterator.lock().unwrap();
-                    let time = duration.lock().unwrap();
-                    self.draw(&mut d, &font, iter.state(), iteration, *time);
-                }
+                let shown = shown.lock().unwrap();
+                self.draw(&mut d, &font, &shown, generation);
             }
 
+            let digit = Self::DIGITS.iter().position(|&k| handle.is_key_pressed(k));
             if handle.is_key_pressed(KeyboardKey::KEY_ENTER) {
-                sender.send(Some(())).unwrap();
-                iteration += 1;
+                generation = match self.input.parse() {
+                    Ok(target) => target,
+                    Err(_) => generation + 1,
+                };
+                self.input.clear();
+            } else if handle.is_key_pressed(KeyboardKey::KEY_BACKSPACE) {
+                if self.input.pop().is_none() {
+                    generation = generation.saturating_sub(1);
+                }
+            } else if let Some(digit) = digit.filter(|&d| d > 0 || !self.input.is_empty()) {
+                self.input.push_str(&digit.to_string());
             } else if handle.is_key_pressed(KeyboardKey::KEY_R) {
-                let mut iter = iterator.lock().unwrap();
-                *iter = self.system.clone().into_iter();
-                let mut duration = duration.lock().unwrap();
-                *duration = 0.0;
-                iteration = 1;
+                generation = 0;
+                self.input.clear();
+            } else if handle.is_key_pressed(KeyboardKey::KEY_F) {
+                self.fit = !self.fit;
+                self.pan_zoom.reset();
+            } else if handle.is_key_pressed(KeyboardKey::KEY_ZERO) {
+                self.fit = true;
+                self.pan_zoom.reset();
+            } else if handle.is_key_pressed(KeyboardKey::KEY_C) {
+                self.orbit = match self.orbit {
+                    Some(_) => None,
+                    None => Some(Orbit::default()),
+                };
+            } else if handle.is_key_pressed(KeyboardKey::KEY_G) {
+                self.config.coloring = self.config.coloring.next();
+                self.coloring = Some(self.config.coloring);
+                // Births weren't tagged, the generation has to be expanded again.
+                if self.config.coloring == Coloring::Generation && !self.system.births() {
+                    self.system = self.system.clone().with_births(true);
+                    sender.send(Message::Replace(self.system.clone())).unwrap();
+                    sender.send(Message::Show(generation)).unwrap();
+                }
             } else if handle.is_key_pressed(KeyboardKey::KEY_P)
                 && handle.is_key_down(KeyboardKey::KEY_LEFT_SUPER)
             {
                 handle.take_screenshot(&thread, "screenshot.png");
             }
+
+            if generation != requested {
+                self.scrubber.extend(generation);
+                sender.send(Message::Show(generation)).unwrap();
+            }
         }
 
-        sender.send(None).unwrap();
+        sender.send(Message::Stop).unwrap();
     }
 
     fn init(&mut self) -> (RaylibHandle, RaylibThread) {
@@ -87,80 +209,187 @@ impl Visualizer {
         self.height = handle.get_screen_height();
     }
 
+    // Parses the watched file again. On failure the last good system stays.
+    fn reload(&mut self) -> Result<(), ParseError> {
+        let path = match &self.watcher {
+            Some(watcher) => watcher.path().to_string_lossy().into_owned(),
+            None => return Ok(()),
+        };
+        let (mut config, system) = parser::parse(&path)?;
+        if config.is_3d() != self.config.is_3d() {
+            self.orbit = config.is_3d().then(Orbit::default);
+        }
+        config.coloring = self.coloring.unwrap_or(config.coloring);
+        let system = system
+            .with_threads(self.system.threads())
+            .with_births(config.coloring == Coloring::Generation);
+        self.system = match self.seed {
+            Some(seed) => system.with_seed(seed),
+            None => system,
+        };
+        self.config = config;
+        self.error = None;
+        Ok(())
+    }
+
     fn load_font(handle: &mut RaylibHandle, thread: &RaylibThread) -> Font {
         handle
             .load_font(thread, Self::FONT_PATH)
             .expect("Couldn't load font.")
     }
 
-    fn create_iterator_channel(
-        &mut self,
-        iterator: &Arc<Mutex<SystemIterator>>,
-        duration: &Arc<Mutex<f64>>,
-    ) -> Sender<Option<()>> {
-        let (sender, reciever) = mpsc::channel::<Option<()>>();
+    // The history lives on its own thread, so that the window stays
+    // responsive while a generation is expanded.
+    fn create_history_channel(&mut self, shown: &Arc<Mutex<Shown>>) -> Sender<Message> {
+        let (sender, reciever) = mpsc::channel::<Message>();
 
-        let iterator_clone = Arc::clone(&iterator);
-        let duration_clone = Arc::clone(&duration);
+        let shown_clone = Arc::clone(shown);
+        let mut history = History::new(self.system.clone(), Self::HISTORY_BUDGET);
 
         thread::spawn(move || loop {
-            match reciever.recv() {
-                Ok(Some(_)) => {
-                    let mut iterator = iterator_clone.lock().unwrap();
-                    let start = Instant::now();
-                    iterator.next().unwrap();
-                    let mut duration = duration_clone.lock().unwrap();
-                    *duration = (Instant::now() - start).as_secs_f64();
+            let mut target = None;
+            let mut message = reciever.recv();
+            // Only the last generation asked for is worth expanding.
+            loop {
+                match message {
+                    Ok(Message::Show(generation)) => target = Some(generation),
+                    Ok(Message::Replace(system)) => {
+                        history = History::new(system, Self::HISTORY_BUDGET)
+                    }
+                    Ok(Message::Stop) => return,
+                    Err(e) => panic!("{}", e),
+                }
+                match reciever.try_recv() {
+                    Ok(next) => message = Ok(next),
+                    Err(_) => break,
                 }
-                Ok(None) => break,
-                Err(e) => panic!("{}", e),
+            }
+
+            if let Some(generation) = target {
+                let start = Instant::now();
+                let state = history.get(generation).clone();
+                let mut shown = shown_clone.lock().unwrap();
+                *shown = Shown {
+                    generation,
+                    state,
+                    duration: (Instant::now() - start).as_secs_f64(),
+                };
             }
         });
 
         sender
     }
 
-    fn draw(
-        &mut self,
-        d: &mut RaylibDrawHandle,
-        font: &Font,
-        state: Option<&State>,
-        iteration: i32,
-        last_duration: f64,
-    ) {
+    fn bar_height(&self) -> i32 {
+        2 * Self::PADDING + self.height / Self::FONT_SCALE
+    }
+
+    fn scrubber_area(&self) -> Rectangle {
+        let height = self.bar_height();
+        Rectangle::new(
+            0.0,
+            (self.height - height) as f32,
+            self.width as f32,
+            height as f32,
+        )
+    }
+
+    // Fits the drawing between the header and the scrubber, or starts the
+    // turtle at the bottom center when fitting is off.
+    fn transform(&self, primitives: &[turtle::Primitive]) -> Transform {
+        let bar = self.bar_height() as f32;
+        match Bounds::of(primitives).filter(|_| self.fit) {
+            Some(bounds) => {
+                let height = self.height as f32 - 2.0 * bar;
+                let mut transform =
+                    Transform::fit(&bounds, self.width as f32, height, Self::FIT_PADDING);
+                transform.y += bar;
+                transform
+            }
+            None => Transform::translate((self.width / 2) as f32, self.height as f32 - bar),
+        }
+    }
+
+    fn draw(&mut self, d: &mut RaylibDrawHandle, font: &Font, shown: &Shown, generation: usize) {
         d.clear_background(Color::new(24, 25, 26, 255));
-        if let Some(state) = state {
-            d.draw_state(
-                state,
-                self.width / 2,
-                self.height,
-                &mut self.config,
-                Color::new(228, 230, 235, 255),
-            );
+        let primitives = turtle::interpret(&shown.state, &self.config);
+        match &self.orbit {
+            Some(orbit) => {
+                let camera = orbit.camera(&primitives);
+                d.draw_primitives_3d(&primitives, camera, Color::new(228, 230, 235, 255));
+            }
+            None => {
+                let transform = self.transform(&primitives);
+                d.draw_primitives(
+                    &primitives,
+                    transform,
+                    self.pan_zoom.camera(),
+                    Color::new(228, 230, 235, 255),
+                );
+            }
         }
         d.draw_rectangle(
             0,
             0,
             self.width,
-            2 * Self::PADDING + self.height / Self::FONT_SCALE,
+            self.bar_height(),
             Color::new(36, 37, 38, 255),
         );
         d.draw_line(
             0,
-            2 * Self::PADDING + self.height / Self::FONT_SCALE,
+            self.bar_height(),
             self.width,
-            2 * Self::PADDING + self.height / Self::FONT_SCALE,
+            self.bar_height(),
             Color::new(228, 230, 235, 255),
         );
-        if state.is_some() {
+
+        let mut header = format!(
+            "N={}, seed={}, took: {:.3}s",
+            shown.generation,
+            self.system.seed(),
+            shown.duration
+        );
+        if self.config.coloring != Coloring::Palette {
+            header.push_str(&format!(", colour: {}", self.config.coloring));
+        }
+        if generation != shown.generation {
+            header.push_str(&format!(", expanding N={}", generation));
+        }
+        if !self.input.is_empty() {
+            header.push_str(&format!(", go to N={}_", self.input));
+        }
+        d.draw_text_ex(
+            font,
+            &header,
+            Vector2::new((2 * Self::PADDING) as f32, Self::PADDING as f32),
+            (self.height / Self::FONT_SCALE) as f32,
+            (Self::PADDING / 2) as f32,
+            Color::new(228, 230, 235, 255),
+        );
+        if let Some(error) = &self.error {
             d.draw_text_ex(
-                &font,
-                &format!("N={}, took: {:.3}s", iteration, last_duration),
-                Vector2::new((2 * Self::PADDING) as f32, Self::PADDING as f32),
+                font,
+                error,
+                Vector2::new(
+                    (2 * Self::PADDING) as f32,
+                    (Self::PADDING + self.bar_height()) as f32,
+                ),
                 (self.height / Self::FONT_SCALE) as f32,
                 (Self::PADDING / 2) as f32,
-                Color::new(228, 230, 235, 255),
+                Color::new(240, 82, 79, 255),
             );
         }
+
+        let area = self.scrubber_area();
+        d.draw_rectangle_rec(area, Color::new(36, 37, 38, 255));
+        d.draw_line(
+            0,
+            area.y as i32,
+            self.width,
+            area.y as i32,
+            Color::new(228, 230, 235, 255),
+        );
+        self.scrubber
+            .draw(d, area, generation, Color::new(228, 230, 235, 255));
     }
 }
@@ -1,3 +1,4 @@
+pub mod export;
 pub mod graphics;
 pub mod macros;
 pub mod parser;
@@ -1,11 +1,7 @@
-use l_system::graphics::Visualizer;
-use l_system::parser;
+mod cli;
 
-fn main() -> Result<(), String> {
-    let (config, system) = parser::parse("systems/branch.lsys")?;
+use std::process::ExitCode;
 
-    let mut visualizer = Visualizer::new("L-Systems", (640, 480), system, config);
-    visualizer.run();
-
-    Ok(())
+fn main() -> ExitCode {
+    cli::run(std::env::args().skip(1))
 }
@@ -0,0 +1,158 @@
+use core::fmt;
+use std::error::Error;
+use std::io;
+use std::ops::Range;
+
+#[derive(Debug)]
+pub enum ParseErrorKind {
+    Io(io::Error),
+    InvalidLine,
+    UnknownSection(String),
+    MissingSections(Vec<&'static str>),
+    UnknownSetting(String),
+    BadSetting(String),
+    NonNumeric {
+        setting: &'static str,
+        expected: &'static str,
+    },
+    BadRule(String),
+    DuplicateRule(String),
+    MissingAxiom,
+}
+
+/// Where in the file an error occurred, `span` being a byte range of `text`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Location {
+    pub line: usize,
+    pub span: Range<usize>,
+    pub text: String,
+}
+
+#[derive(Debug)]
+pub struct ParseError {
+    pub kind: ParseErrorKind,
+    pub path: Option<String>,
+    pub location: Option<Location>,
+}
+
+impl ParseError {
+    pub fn new(kind: ParseErrorKind) -> Self {
+        Self {
+            kind,
+            path: None,
+            location: None,
+        }
+    }
+
+    pub fn at(mut self, line: usize, span: Range<usize>, text: &str) -> Self {
+        self.location = Some(Location {
+            line,
+            span,
+            text: text.to_string(),
+        });
+        self
+    }
+
+    pub fn in_file(mut self, path: &str) -> Self {
+        self.path = Some(path.to_string());
+        self
+    }
+
+    /// 1-based line and column of the error, if it has a location.
+    pub fn position(&self) -> Option<(usize, usize)> {
+        self.location.as_ref().map(|l| {
+            let column = l.text[..l.span.start.min(l.text.len())].chars().count() + 1;
+            (l.line, column)
+        })
+    }
+
+    /// Formats the error together with the offending line, underlining
+    /// the span with carets:
+    ///
+    /// ```text
+    /// error: Unrecognized config setting: `line_lenght`.
+    ///  --> systems/branch.lsys:2:1
+    ///   |
+    /// 2 | line_lenght = 3
+    ///   | ^^^^^^^^^^^
+    /// ```
+    pub fn render(&self) -> String {
+        let mut rendered = format!("error: {}", self.kind);
+        let path = self.path.as_deref().unwrap_or("<input>");
+
+        let Some(location) = &self.location else {
+            rendered.push_str(&format!("\n --> {}", path));
+            return rendered;
+        };
+        let (line, column) = self.position().unwrap_or((location.line, 1));
+        let gutter = " ".repeat(line.to_string().len());
+        let end = location.span.end.min(location.text.len());
+        let start = location.span.start.min(end);
+        let width = location.text[start..end].chars().count().max(1);
+
+        rendered.push_str(&format!(
+            "\n{gutter}--> {path}:{line}:{column}\n{gutter} |\n{line} | {text}\n{gutter} | {pad}{carets}",
+            gutter = gutter,
+            path = path,
+            line = line,
+            column = column,
+            text = location.text,
+            pad = " ".repeat(column - 1),
+            carets = "^".repeat(width),
+        ));
+        rendered
+    }
+}
+
+impl fmt::Display for ParseErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "Could not read file: {}.", e),
+            Self::InvalidLine => {
+                write!(f, "Invalid line, expected a `[section]` or a `#` comment.")
+            }
+            Self::UnknownSection(s) => write!(f, "Unrecognized section name: `{}`.", s),
+            Self::MissingSections(sections) => {
+                write!(f, "Missing sections: `[{}]`.", sections.join("]`, `["))
+            }
+            Self::UnknownSetting(s) => write!(f, "Unrecognized config setting: `{}`.", s),
+            Self::BadSetting(s) => write!(f, "{}", s),
+            Self::NonNumeric { setting, expected } => {
+                write!(f, "`{}` should be {}.", setting, expected)
+            }
+            Self::BadRule(s) => write!(f, "{}", s),
+            Self::DuplicateRule(s) => write!(f, "Duplicate rule for `{}`.", s),
+            Self::MissingAxiom => write!(f, "Could not find `axiom`."),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(path) = &self.path {
+            write!(f, "{}:", path)?;
+        }
+        if let Some((line, column)) = self.position() {
+            write!(f, "{}:{}:", line, column)?;
+        }
+        if self.path.is_some() || self.location.is_some() {
+            write!(f, " ")?;
+        }
+        write!(f, "{}", self.kind)
+    }
+}
+
+impl Error for ParseError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match &self.kind {
+            ParseErrorKind::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ParseError {
+    fn from(e: io::Error) -> Self {
+        Self::new(ParseErrorKind::Io(e))
+    }
+}
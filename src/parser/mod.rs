@@ -0,0 +1,479 @@
+pub mod error;
+mod modules;
+mod writer;
+
+use crate::graphics::{Config, Rgba};
+use crate::system::{Expression, Production, Rules, State, System};
+use core::fmt;
+pub use error::{ParseError, ParseErrorKind};
+pub use modules::{parse_patterns, parse_state, parse_templates};
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::ops::Range;
+use std::str::FromStr;
+pub use writer::{save, write};
+
+type Result<T> = std::result::Result<T, ParseError>;
+
+struct LineIter<R> {
+    lines: io::Lines<R>,
+    number: usize,
+    current: String,
+}
+
+impl<R: BufRead> LineIter<R> {
+    fn new(reader: R) -> Self {
+        Self {
+            lines: reader.lines(),
+            number: 0,
+            current: String::new(),
+        }
+    }
+
+    fn next_line(&mut self) -> Result<Option<String>> {
+        match self.lines.next() {
+            None => Ok(None),
+            Some(Err(e)) => Err(ParseError::from(e).at(self.number + 1, 0..0, "")),
+            Some(Ok(line)) => {
+                self.number += 1;
+                self.current.clone_from(&line);
+                Ok(Some(line))
+            }
+        }
+    }
+
+    /// An error located at `part`, a slice of the current `line`.
+    fn error(&self, kind: ParseErrorKind, line: &str, part: &str) -> ParseError {
+        ParseError::new(kind).at(self.number, span(line, part), &self.current)
+    }
+}
+
+fn span(line: &str, part: &str) -> Range<usize> {
+    let start = (part.as_ptr() as usize).wrapping_sub(line.as_ptr() as usize);
+    match start <= line.len() && start + part.len() <= line.len() {
+        true => start..start + part.len(),
+        false => 0..line.len(),
+    }
+}
+
+enum Section {
+    Config,
+    Palette,
+    Rules,
+    Start,
+}
+
+impl FromStr for Section {
+    type Err = ParseErrorKind;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "config" => Ok(Self::Config),
+            "palette" => Ok(Self::Palette),
+            "rules" => Ok(Self::Rules),
+            "start" => Ok(Self::Start),
+            _ => Err(ParseErrorKind::UnknownSection(s.to_string())),
+        }
+    }
+}
+
+/// A parsed `.lsys` file.
+#[derive(Debug)]
+pub struct LSystem {
+    pub config: Config,
+    pub system: System,
+}
+
+impl FromStr for LSystem {
+    type Err = ParseError;
+
+    fn from_str(source: &str) -> Result<Self> {
+        let (config, system) = parse_str(source)?;
+        Ok(Self { config, system })
+    }
+}
+
+impl fmt::Display for LSystem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writer::write_lsys(f, &self.config, &self.system)
+    }
+}
+
+impl From<LSystem> for (Config, System) {
+    fn from(l_system: LSystem) -> Self {
+        (l_system.config, l_system.system)
+    }
+}
+
+pub fn parse(file_path: &str) -> Result<(Config, System)> {
+    File::open(file_path)
+        .map_err(ParseError::from)
+        .and_then(|file| parse_reader(BufReader::new(file)))
+        .map_err(|e| e.in_file(file_path))
+}
+
+pub fn parse_str(source: &str) -> Result<(Config, System)> {
+    parse_reader(source.as_bytes())
+}
+
+pub fn parse_reader<R: BufRead>(reader: R) -> Result<(Config, System)> {
+    let mut lines = LineIter::new(reader);
+    let mut config: Option<Config> = None;
+    let mut ignore = State::new();
+    let mut rules: Option<Rules> = None;
+    let mut start: Option<(State, u64)> = None;
+    let mut palette = Vec::new();
+
+    while let Some(line) = lines.next_line()? {
+        match line {
+            ref line if is_header(line) => {
+                let name = &line[1..line.len() - 1];
+                match Section::from_str(name).map_err(|kind| lines.error(kind, line, name))? {
+                    Section::Config => {
+                        let (parsed, ignored) = parse_config(&mut lines)?;
+                        config = Some(parsed);
+                        ignore = ignored;
+                    }
+                    Section::Palette => palette = parse_palette(&mut lines)?,
+                    Section::Rules => rules = Some(parse_rules(&mut lines)?),
+                    Section::Start => start = Some(parse_start(&mut lines)?),
+                }
+            }
+            ref line if is_comment(line) => continue,
+            ref line => return Err(lines.error(ParseErrorKind::InvalidLine, line, line)),
+        }
+    }
+
+    match (config, rules, start) {
+        (Some(mut config), Some(rules), Some((axiom, seed))) => {
+            config.palette = palette;
+            let system = System::stochastic(rules, axiom, seed).with_ignore(ignore);
+            Ok((config, system))
+        }
+        (config, rules, start) => {
+            let missing = [
+                (config.is_none(), "config"),
+                (rules.is_none(), "rules"),
+                (start.is_none(), "start"),
+            ];
+            let missing = missing
+                .into_iter()
+                .filter_map(|(missing, name)| missing.then_some(name))
+                .collect();
+            Err(ParseError::new(ParseErrorKind::MissingSections(missing)))
+        }
+    }
+}
+
+fn is_header(line: &str) -> bool {
+    line.starts_with("[") && line.ends_with("]")
+}
+
+fn is_comment(line: &str) -> bool {
+    line.starts_with('#')
+}
+
+enum ConfigSetting {
+    LineLength,
+    LineWidthIncrement,
+    LineLengthScaleFactor,
+    TurningAngle,
+    TurningAngleIncrement,
+    Dimensions,
+    FillColor,
+    Coloring,
+    Gradient,
+    Ignore,
+}
+
+impl FromStr for ConfigSetting {
+    type Err = ParseErrorKind;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "line_length" => Ok(Self::LineLength),
+            "line_width_increment" => Ok(Self::LineWidthIncrement),
+            "line_length_scale_factor" => Ok(Self::LineLengthScaleFactor),
+            "turning_angle" => Ok(Self::TurningAngle),
+            "turning_angle_increment" => Ok(Self::TurningAngleIncrement),
+            "dimensions" => Ok(Self::Dimensions),
+            "fill_color" => Ok(Self::FillColor),
+            "coloring" => Ok(Self::Coloring),
+            "gradient" => Ok(Self::Gradient),
+            "ignore" => Ok(Self::Ignore),
+            _ => Err(ParseErrorKind::UnknownSetting(s.to_string())),
+        }
+    }
+}
+
+fn parse_config<R: BufRead>(lines: &mut LineIter<R>) -> Result<(Config, State)> {
+    let mut config = Config::default();
+    let mut ignore = State::new();
+
+    while let Some(line) = lines.next_line()? {
+        if line.trim().is_empty() {
+            break;
+        }
+
+        let (setting, value) = parse_assigment(lines, &line, "=")?;
+        let setting =
+            ConfigSetting::from_str(setting).map_err(|kind| lines.error(kind, &line, setting))?;
+        let non_numeric = |setting, expected| {
+            let kind = ParseErrorKind::NonNumeric { setting, expected };
+            lines.error(kind, &line, value)
+        };
+        match setting {
+            ConfigSetting::LineLength => {
+                config.line_length = value
+                    .parse()
+                    .map_err(|_| non_numeric("line_length", "an integer"))?
+            }
+            ConfigSetting::LineWidthIncrement => {
+                config.line_width_increment = value
+                    .parse()
+                    .map_err(|_| non_numeric("line_width_increment", "a number"))?
+            }
+            ConfigSetting::LineLengthScaleFactor => {
+                config.line_length_scale_factor = value
+                    .parse()
+                    .map_err(|_| non_numeric("line_length_scale_factor", "a number"))?
+            }
+            ConfigSetting::TurningAngle => {
+                config.turning_angle = value
+                    .parse::<f32>()
+                    .map_err(|_| non_numeric("turning_angle", "a number"))?
+                    .to_radians();
+            }
+            ConfigSetting::TurningAngleIncrement => {
+                config.turning_angle_increment = value
+                    .parse::<f32>()
+                    .map_err(|_| non_numeric("turning_angle_increment", "a number"))?
+                    .to_radians();
+            }
+            ConfigSetting::Dimensions => {
+                config.dimensions = match value {
+                    "2" | "3" => value.parse().unwrap(),
+                    _ => return Err(non_numeric("dimensions", "2 or 3")),
+                }
+            }
+            ConfigSetting::FillColor => {
+                config.fill_color = value
+                    .parse()
+                    .map_err(|e| lines.error(ParseErrorKind::BadSetting(e), &line, value))?
+            }
+            ConfigSetting::Coloring => {
+                config.coloring = value
+                    .parse()
+                    .map_err(|e| lines.error(ParseErrorKind::BadSetting(e), &line, value))?
+            }
+            ConfigSetting::Gradient => {
+                config.gradient = value
+                    .split_whitespace()
+                    .map(|stop| {
+                        let bad_setting =
+                            |e| lines.error(ParseErrorKind::BadSetting(e), &line, stop);
+                        stop.parse().map_err(bad_setting)
+                    })
+                    .collect::<Result<_>>()?;
+                if config.gradient.is_empty() {
+                    let message = "Gradient: expected at least one colour.".to_string();
+                    return Err(lines.error(ParseErrorKind::BadSetting(message), &line, value));
+                }
+            }
+            ConfigSetting::Ignore => {
+                ignore = parse_state(value)
+                    .map_err(|e| lines.error(ParseErrorKind::BadSetting(e), &line, value))?
+            }
+        }
+    }
+    Ok((config, ignore))
+}
+
+// Colours are numbered from 0, in order: `0 = #8b5a2b`.
+fn parse_palette<R: BufRead>(lines: &mut LineIter<R>) -> Result<Vec<Rgba>> {
+    let mut palette = Vec::new();
+
+    while let Some(line) = lines.next_line()? {
+        if line.trim().is_empty() {
+            break;
+        }
+        let (index, value) = parse_assigment(lines, &line, "=")?;
+        let bad_setting = |e, part| lines.error(ParseErrorKind::BadSetting(e), &line, part);
+        if index.parse() != Ok(palette.len()) {
+            let message = format!(
+                "Palette: expected colour `{}`, found `{}`.",
+                palette.len(),
+                index
+            );
+            return Err(bad_setting(message, index));
+        }
+        palette.push(value.parse().map_err(|e| bad_setting(e, value))?);
+    }
+    Ok(palette)
+}
+
+fn parse_assigment<'a, R: BufRead>(
+    lines: &LineIter<R>,
+    line: &'a str,
+    delimiter: &str,
+) -> Result<(&'a str, &'a str)> {
+    if line.matches(delimiter).count() != 1 {
+        let message = format!("Expected exactly one `{}` in this line.", delimiter);
+        let kind = match delimiter {
+            "->" => ParseErrorKind::BadRule(message),
+            _ => ParseErrorKind::BadSetting(message),
+        };
+        return Err(lines.error(kind, line, line));
+    }
+
+    let (key, value) = line.split_once(delimiter).unwrap();
+
+    Ok((key.trim(), value.trim()))
+}
+
+fn parse_rules<R: BufRead>(lines: &mut LineIter<R>) -> Result<Rules> {
+    let mut rules: Rules = HashMap::new();
+    let mut deterministic = HashSet::new();
+
+    while let Some(line) = lines.next_line()? {
+        if line.trim().is_empty() {
+            break;
+        }
+        let bad_rule = |e, part| lines.error(ParseErrorKind::BadRule(e), &line, part);
+
+        let (_, successor) = parse_assigment(lines, &line, "->")?;
+        let (arrow, _) = line.split_once("->").unwrap();
+        let (lhs, weight) = parse_weight(arrow).map_err(|e| bad_rule(e, arrow.trim()))?;
+        let (predecessor, condition) = match lhs.split_once(':') {
+            Some((predecessor, condition)) => (predecessor.trim(), Some(condition.trim())),
+            None => (lhs, None),
+        };
+        let (left, predecessor, right) = parse_context(predecessor);
+
+        let mut patterns = parse_patterns(predecessor).map_err(|e| bad_rule(e, predecessor))?;
+        if patterns.len() != 1 {
+            let message = format!("Predecessor: `{}` isn't a single module.", predecessor);
+            return Err(bad_rule(message, predecessor));
+        }
+        let predecessor = patterns.remove(0);
+
+        // Only stochastic rules may share a predecessor (and its context and
+        // condition), any other repetition would silently shadow a rule.
+        if weight.is_none() && !deterministic.insert(lhs.replace(char::is_whitespace, "")) {
+            let kind = ParseErrorKind::DuplicateRule(lhs.to_string());
+            return Err(lines.error(kind, &line, lhs));
+        }
+
+        let mut production = Production::weighted(State::new(), weight.unwrap_or(1.0));
+        production.left = parse_patterns(left).map_err(|e| bad_rule(e, left))?;
+        production.params = predecessor.params;
+        production.right = parse_patterns(right).map_err(|e| bad_rule(e, right))?;
+
+        let condition = match condition {
+            Some(condition) => Some(
+                Expression::parse(condition, &production.parameters())
+                    .map_err(|e| bad_rule(e, condition))?,
+            ),
+            None => None,
+        };
+        let successor = parse_templates(successor, &production.parameters())
+            .map_err(|e| bad_rule(e, successor))?;
+        production.condition = condition;
+        production.successor = successor;
+
+        rules
+            .entry(predecessor.symbol)
+            .or_default()
+            .push(production);
+    }
+
+    Ok(rules)
+}
+
+// A stochastic rule carries its weight in the arrow: `F -(0.33)-> F[+F]F`.
+// `before` is everything up to `->`, untrimmed, as only a `-(<weight>)`
+// touching the `->` is read as a weight: `A > -(x) -> B` has a right context.
+fn parse_weight(before: &str) -> std::result::Result<(&str, Option<f32>), String> {
+    let Some((predecessor, weight)) = before
+        .strip_suffix(')')
+        .and_then(|p| p.rsplit_once("-("))
+        .filter(|(_, weight)| !weight.contains(['(', ')']))
+    else {
+        return Ok((before.trim(), None));
+    };
+
+    match weight.trim().parse::<f32>() {
+        Ok(weight) if weight.is_finite() && weight >= 0.0 => Ok((predecessor.trim(), Some(weight))),
+        _ => Err(format!(
+            "Rule weight: `{}` should be a non-negative number.",
+            weight
+        )),
+    }
+}
+
+// A context-sensitive predecessor is written as `left < symbol > right`,
+// where either of the contexts may be omitted. A lone `<` or `>` is the
+// predecessor itself.
+fn parse_context(predecessor: &str) -> (&str, &str, &str) {
+    if predecessor.chars().count() <= 1 {
+        return ("", predecessor, "");
+    }
+    let (left, rest) = predecessor.split_once('<').unwrap_or(("", predecessor));
+    let (symbol, right) = rest.split_once('>').unwrap_or((rest, ""));
+    (left.trim(), symbol.trim(), right.trim())
+}
+
+enum StartSetting {
+    Axiom,
+    Seed,
+    // TODO: Position and Angle
+}
+
+impl FromStr for StartSetting {
+    type Err = ParseErrorKind;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "axiom" => Ok(Self::Axiom),
+            "seed" => Ok(Self::Seed),
+            _ => Err(ParseErrorKind::UnknownSetting(s.to_string())),
+        }
+    }
+}
+
+fn parse_start<R: BufRead>(lines: &mut LineIter<R>) -> Result<(State, u64)> {
+    let mut axiom: Option<State> = None;
+    let mut seed = 0;
+
+    while let Some(line) = lines.next_line()? {
+        if line.trim().is_empty() {
+            break;
+        }
+        let (setting, value) = parse_assigment(lines, &line, "=")?;
+        let setting =
+            StartSetting::from_str(setting).map_err(|kind| lines.error(kind, &line, setting))?;
+        match setting {
+            StartSetting::Axiom => {
+                axiom = Some(
+                    parse_state(value)
+                        .map_err(|e| lines.error(ParseErrorKind::BadSetting(e), &line, value))?,
+                )
+            }
+            StartSetting::Seed => {
+                seed = value.parse().map_err(|_| {
+                    let kind = ParseErrorKind::NonNumeric {
+                        setting: "seed",
+                        expected: "a non-negative integer",
+                    };
+                    lines.error(kind, &line, value)
+                })?
+            }
+        }
+    }
+
+    match axiom {
+        None => Err(ParseError::new(ParseErrorKind::MissingAxiom)),
+        Some(a) => Ok((a, seed)),
+    }
+}
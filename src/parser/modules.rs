@@ -0,0 +1,133 @@
+// Parsing of module strings, such as `F(l)[+A(l*0.7,w)]`.
+//
+// A `(` directly following a symbol opens its parameter list when it is
+// closed and every argument reads as an expression, so that Bourke strings
+// like `F(+F)` or `X()` keep their `(` and `)` symbols. Anywhere else `(` is
+// the symbol itself. To turn by the turning angle increment right before
+// what would read as parameters, separate the two with whitespace: `F (x)`.
+
+use crate::system::{Expression, Module, Pattern, State, Symbol, Template};
+
+type Token<'a> = (Symbol, Vec<&'a str>);
+
+fn tokenize(text: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((_, c)) = chars.next() {
+        if c.is_whitespace() {
+            continue;
+        }
+        let mut args = Vec::new();
+        if let Some(&(open, '(')) = chars.peek() {
+            if let Some(close) = matching_paren(text, open) {
+                let inner = split_args(&text[open + 1..close]);
+                if inner.iter().all(|arg| Expression::is_well_formed(arg)) {
+                    args = inner;
+                    while chars.next_if(|&(i, _)| i <= close).is_some() {}
+                }
+            }
+        }
+        tokens.push((Symbol::from(c), args));
+    }
+
+    tokens
+}
+
+fn matching_paren(text: &str, open: usize) -> Option<usize> {
+    let mut depth = 0;
+    for (i, c) in text[open..].char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(open + i);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+fn split_args(text: &str) -> Vec<&str> {
+    let mut args = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in text.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                args.push(text[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    args.push(text[start..].trim());
+    args
+}
+
+// Parameters that read as expressions but don't fit may have been meant as
+// symbols.
+fn hint(symbol: Symbol, error: String) -> String {
+    format!(
+        "{} To use `(` as a symbol, separate it from `{}` with whitespace.",
+        error, symbol
+    )
+}
+
+/// Parses a state whose parameters are constant expressions, like an axiom.
+pub fn parse_state(text: &str) -> Result<State, String> {
+    tokenize(text)
+        .into_iter()
+        .map(|(symbol, args)| {
+            let params = args
+                .into_iter()
+                .map(|arg| Ok(Expression::parse(arg, &[])?.eval(&[])))
+                .collect::<Result<_, String>>()
+                .map_err(|e| hint(symbol, e))?;
+            Ok(Module::new(symbol, params))
+        })
+        .collect()
+}
+
+/// Parses a predecessor or a context, whose parameters are formal names.
+pub fn parse_patterns(text: &str) -> Result<Vec<Pattern>, String> {
+    tokenize(text)
+        .into_iter()
+        .map(|(symbol, args)| {
+            let params = args
+                .into_iter()
+                .map(|arg| match is_identifier(arg) {
+                    true => Ok(arg.to_string()),
+                    false => Err(format!("Parameter: `{}` isn't a valid name.", arg)),
+                })
+                .collect::<Result<_, String>>()
+                .map_err(|e| hint(symbol, e))?;
+            Ok(Pattern { symbol, params })
+        })
+        .collect()
+}
+
+/// Parses a successor, whose parameters are expressions over `parameters`.
+pub fn parse_templates(text: &str, parameters: &[&str]) -> Result<Vec<Template>, String> {
+    tokenize(text)
+        .into_iter()
+        .map(|(symbol, args)| {
+            let args = args
+                .into_iter()
+                .map(|arg| Expression::parse(arg, parameters))
+                .collect::<Result<_, String>>()
+                .map_err(|e| hint(symbol, e))?;
+            Ok(Template { symbol, args })
+        })
+        .collect()
+}
+
+fn is_identifier(text: &str) -> bool {
+    text.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && text.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
@@ -0,0 +1,168 @@
+// Writing a `(Config, System)` back to the `.lsys` format, such that
+// `parser::parse_str` reads it as the same system.
+//
+// The output is canonical: config settings in a fixed order, the
+// `[palette]` if any, rules sorted by predecessor symbol (the productions of
+// a symbol keep their order, as stochastic selection depends on it) and the
+// `[start]` section last.
+
+use crate::graphics::{Coloring, Config, Rgba};
+use crate::system::{Expression, Module, Pattern, Production, State, Symbol, System, Template};
+use std::fmt::{self, Write};
+use std::fs;
+use std::io;
+
+pub fn write(config: &Config, system: &System) -> String {
+    let mut text = String::new();
+    // Writing into a `String` can't fail.
+    let _ = write_lsys(&mut text, config, system);
+    text
+}
+
+pub fn save(path: &str, config: &Config, system: &System) -> io::Result<()> {
+    fs::write(path, write(config, system))
+}
+
+pub(super) fn write_lsys(f: &mut impl Write, config: &Config, system: &System) -> fmt::Result {
+    writeln!(f, "[config]")?;
+    writeln!(f, "line_length = {}", config.line_length)?;
+    writeln!(f, "line_width_increment = {}", config.line_width_increment)?;
+    writeln!(
+        f,
+        "line_length_scale_factor = {}",
+        config.line_length_scale_factor
+    )?;
+    writeln!(f, "turning_angle = {}", degrees(config.turning_angle))?;
+    writeln!(
+        f,
+        "turning_angle_increment = {}",
+        degrees(config.turning_angle_increment)
+    )?;
+    if config.is_3d() {
+        writeln!(f, "dimensions = {}", config.dimensions)?;
+    }
+    if config.fill_color != Config::DEFAULT_FILL {
+        writeln!(f, "fill_color = {}", config.fill_color)?;
+    }
+    if config.coloring != Coloring::Palette {
+        writeln!(f, "coloring = {}", config.coloring)?;
+    }
+    if config.gradient != Config::DEFAULT_GRADIENT {
+        let stops: Vec<_> = config.gradient.iter().map(Rgba::to_string).collect();
+        writeln!(f, "gradient = {}", stops.join(" "))?;
+    }
+    if !system.ignore().is_empty() {
+        writeln!(f, "ignore = {}", state(system.ignore()))?;
+    }
+
+    if !config.palette.is_empty() {
+        writeln!(f, "\n[palette]")?;
+        for (index, color) in config.palette.iter().enumerate() {
+            writeln!(f, "{} = {}", index, color)?;
+        }
+    }
+
+    writeln!(f, "\n[rules]")?;
+    let mut rules: Vec<_> = system.rules().iter().collect();
+    rules.sort_by_key(|(symbol, _)| symbol.to_string());
+    for (symbol, productions) in rules {
+        let sides: Vec<_> = productions
+            .iter()
+            .map(|production| predecessor(symbol, production))
+            .collect();
+        for (production, side) in productions.iter().zip(&sides) {
+            // Unweighted rules can't share a predecessor, so the weight is
+            // kept whenever it isn't the default or the side is repeated.
+            let shared = sides.iter().filter(|other| *other == side).count() > 1;
+            let arrow = match production.weight != 1.0 || shared {
+                true => format!("-({})->", production.weight),
+                false => "->".to_string(),
+            };
+            let successor = successor(&production.successor, &production.parameters());
+            let rule = format!("{} {} {}", side, arrow, successor);
+            writeln!(f, "{}", rule.trim_end())?;
+        }
+    }
+
+    writeln!(f, "\n[start]")?;
+    writeln!(f, "axiom = {}", state(system.start()))?;
+    writeln!(f, "seed = {}", system.seed())
+}
+
+// The shortest decimal reading back as the same angle in radians.
+fn degrees(radians: f32) -> f32 {
+    let degrees = radians.to_degrees();
+    (0..9)
+        .map(|precision| format!("{:.*}", precision, degrees))
+        .filter_map(|text| text.parse::<f32>().ok())
+        .find(|d| d.to_radians() == radians)
+        .unwrap_or(degrees)
+}
+
+fn predecessor(symbol: &Symbol, production: &Production) -> String {
+    let mut side = String::new();
+    if !production.left.is_empty() {
+        side.push_str(&format!("{} < ", patterns(&production.left)));
+    }
+    side.push_str(&patterns(&[Pattern {
+        symbol: *symbol,
+        params: production.params.clone(),
+    }]));
+    if !production.right.is_empty() {
+        side.push_str(&format!(" > {}", patterns(&production.right)));
+    }
+    if let Some(condition) = &production.condition {
+        side.push_str(&format!(
+            " : {}",
+            condition.display(&production.parameters())
+        ));
+    }
+    side
+}
+
+fn state(state: &State) -> String {
+    modules(state.iter().map(|Module { symbol, params, .. }| {
+        let args = params.iter().map(f32::to_string).collect();
+        (symbol, args)
+    }))
+}
+
+fn patterns(patterns: &[Pattern]) -> String {
+    modules(
+        patterns
+            .iter()
+            .map(|Pattern { symbol, params }| (symbol, params.clone())),
+    )
+}
+
+fn successor(templates: &[Template], parameters: &[&str]) -> String {
+    modules(templates.iter().map(|Template { symbol, args }| {
+        let args = args
+            .iter()
+            .map(|arg: &Expression| arg.display(parameters).to_string())
+            .collect();
+        (symbol, args)
+    }))
+}
+
+// Joins modules, separating the pairs the tokenizer would read differently:
+// a `(` symbol after any module would open its parameter list, and `->`
+// would be taken for the arrow of a rule.
+fn modules<'a>(modules: impl Iterator<Item = (&'a Symbol, Vec<String>)>) -> String {
+    let mut text = String::new();
+    let mut previous: Option<&Symbol> = None;
+    for (symbol, args) in modules {
+        if matches!(
+            (previous, symbol),
+            (Some(_), Symbol::DecAngle) | (Some(Symbol::Right), Symbol::MulLine)
+        ) {
+            text.push(' ');
+        }
+        text.push_str(&symbol.to_string());
+        if !args.is_empty() {
+            text.push_str(&format!("({})", args.join(",")));
+        }
+        previous = Some(symbol);
+    }
+    text
+}
@@ -0,0 +1,137 @@
+use crate::system::{parallel, production, random, Module, Rules, State, Symbol};
+use std::collections::HashMap;
+use std::ops::Range;
+use std::slice;
+
+/// The productions of a system in a table indexed by dense symbol ids.
+///
+/// Each symbol of the system is interned as a `u16`. Without parameters,
+/// conditions or context, rewriting a symbol then takes an index into the
+/// table and a copy of the ids of a successor, instead of hashing the symbol
+/// and instantiating the successor module by module.
+#[derive(Debug, Clone)]
+pub struct Compact {
+    symbols: Vec<Symbol>,
+    ids: HashMap<Symbol, u16>,
+    // Indexed by id, empty for symbols without productions.
+    table: Vec<Entry>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Entry {
+    weights: Vec<f32>,
+    successors: Vec<Vec<u16>>,
+}
+
+impl Compact {
+    /// `None` when a production has parameters, a condition or context, or
+    /// when there are more symbols than ids.
+    pub fn new(rules: &Rules) -> Option<Self> {
+        let mut compact = Self {
+            symbols: Vec::new(),
+            ids: HashMap::new(),
+            table: Vec::new(),
+        };
+        for (symbol, productions) in rules {
+            let id = compact.intern(*symbol)?;
+            let mut entry = Entry::default();
+            for production in productions {
+                if !production.is_trivial() {
+                    return None;
+                }
+                let successor = production
+                    .successor
+                    .iter()
+                    .map(|t| match t.args.is_empty() {
+                        true => compact.intern(t.symbol),
+                        false => None,
+                    })
+                    .collect::<Option<_>>()?;
+                entry.weights.push(production.weight);
+                entry.successors.push(successor);
+            }
+            compact.table[id as usize] = entry;
+        }
+        Some(compact)
+    }
+
+    /// The ids of `state`, or `None` if one of its modules has parameters.
+    pub fn encode(&mut self, state: &[Module]) -> Option<Vec<u16>> {
+        state
+            .iter()
+            .map(|m| match m.params.is_empty() {
+                true => self.intern(m.symbol),
+                false => None,
+            })
+            .collect()
+    }
+
+    pub fn decode(&self, ids: &[u16]) -> State {
+        ids.iter()
+            .map(|&id| Module::from(self.symbols[id as usize]))
+            .collect()
+    }
+
+    /// Appends the successor of `state` to `output`, `state` being the given
+    /// generation of a system with the given seed.
+    pub fn rewrite(&self, state: &[u16], seed: u64, generation: u64, output: &mut Vec<u16>) {
+        for (index, id) in state.iter().enumerate() {
+            output.extend_from_slice(self.successor(id, index, seed, generation));
+        }
+    }
+
+    /// Replaces `output` with the successor of `state`, rewritten on up to
+    /// `threads` threads. The result is the same as with [`rewrite`](Self::rewrite).
+    pub fn rewrite_parallel(
+        &self,
+        state: &[u16],
+        seed: u64,
+        generation: u64,
+        threads: usize,
+        output: &mut Vec<u16>,
+    ) {
+        let chunks = parallel::chunks(state.len(), threads);
+        if chunks.len() < 2 {
+            output.clear();
+            return self.rewrite(state, seed, generation, output);
+        }
+        let successors = |chunk: Range<usize>| {
+            let start = chunk.start;
+            state[chunk]
+                .iter()
+                .enumerate()
+                .map(move |(i, id)| self.successor(id, start + i, seed, generation))
+        };
+        parallel::rewrite(
+            &chunks,
+            |chunk| (successors(chunk.clone()).map(<[u16]>::len).sum(), chunk),
+            |chunk, part| successors(chunk).for_each(|successor| part.extend_from_slice(successor)),
+            output,
+        );
+    }
+
+    // What the symbol `id` at `index` of `generation` is rewritten into.
+    fn successor<'a>(&'a self, id: &'a u16, index: usize, seed: u64, generation: u64) -> &'a [u16] {
+        let entry = &self.table[*id as usize];
+        match entry.successors.as_slice() {
+            [] => slice::from_ref(id),
+            [successor] => successor,
+            successors => {
+                let sample = random::uniform(seed, generation, index as u64);
+                let chosen = production::pick(entry.weights.iter().copied(), sample);
+                &successors[chosen.unwrap_or_default()]
+            }
+        }
+    }
+
+    fn intern(&mut self, symbol: Symbol) -> Option<u16> {
+        if let Some(&id) = self.ids.get(&symbol) {
+            return Some(id);
+        }
+        let id = u16::try_from(self.symbols.len()).ok()?;
+        self.symbols.push(symbol);
+        self.ids.insert(symbol, id);
+        self.table.push(Entry::default());
+        Some(id)
+    }
+}
@@ -0,0 +1,118 @@
+// Context matching for 1L / 2L systems, as described in ABOP (section 1.8).
+//
+// Branches are transparent when looking for context: the left context of a
+// symbol is found along the path towards the root (skipping `[` and whole
+// sibling branches), and the right context among its successors (skipping
+// whole branches that start after it). Symbols in the ignore list are
+// skipped as if they were not there.
+
+use crate::system::production::Pattern;
+use crate::system::{Module, State, Symbol};
+
+/// For every `[` and `]` in `state`, the index of its matching bracket.
+/// Unbalanced brackets point at themselves.
+pub fn matching_brackets(state: &[Module]) -> Vec<usize> {
+    let mut matches: Vec<usize> = (0..state.len()).collect();
+    let mut stack = Vec::new();
+    for (index, module) in state.iter().enumerate() {
+        match module.symbol {
+            Symbol::Push => stack.push(index),
+            Symbol::Pop => {
+                if let Some(open) = stack.pop() {
+                    matches[open] = index;
+                    matches[index] = open;
+                }
+            }
+            _ => {}
+        }
+    }
+    matches
+}
+
+fn is_ignored(ignore: &State, symbol: &Symbol) -> bool {
+    ignore.iter().any(|m| m.symbol == *symbol)
+}
+
+/// Matches `context` to the left of `index`, pushing the positions of the
+/// matched modules (right to left) onto `matched`.
+pub fn left_matches(
+    context: &[Pattern],
+    state: &[Module],
+    brackets: &[usize],
+    index: usize,
+    ignore: &State,
+    matched: &mut Vec<usize>,
+) -> bool {
+    let mut position = index;
+    for expected in context.iter().rev() {
+        loop {
+            if position == 0 {
+                return false;
+            }
+            position -= 1;
+            match &state[position].symbol {
+                Symbol::Push => continue,
+                Symbol::Pop => position = brackets[position],
+                symbol if is_ignored(ignore, symbol) => continue,
+                _ if expected.matches(&state[position]) => break,
+                _ => return false,
+            }
+        }
+        matched.push(position);
+    }
+    true
+}
+
+/// Matches `context` to the right of `index`, pushing the positions of the
+/// matched modules (left to right, brackets excluded) onto `matched`.
+pub fn right_matches(
+    context: &[Pattern],
+    state: &[Module],
+    brackets: &[usize],
+    index: usize,
+    ignore: &State,
+    matched: &mut Vec<usize>,
+) -> bool {
+    let mut position = index + 1;
+    let mut expected = context.iter().peekable();
+    while let Some(&next) = expected.peek() {
+        let Some(module) = state.get(position) else {
+            return false;
+        };
+        match (&next.symbol, &module.symbol) {
+            (Symbol::Push, Symbol::Push) => {
+                expected.next();
+            }
+            (Symbol::Pop, _) => {
+                // The context leaves the branch, continue after its end.
+                let Some(end) = end_of_branch(state, brackets, position) else {
+                    return false;
+                };
+                position = end;
+                expected.next();
+            }
+            (_, Symbol::Push) => position = brackets[position],
+            (_, Symbol::Pop) => return false,
+            (_, symbol) if is_ignored(ignore, symbol) => {}
+            _ if next.matches(module) => {
+                matched.push(position);
+                expected.next();
+            }
+            _ => return false,
+        }
+        position += 1;
+    }
+    true
+}
+
+fn end_of_branch(state: &[Module], brackets: &[usize], mut position: usize) -> Option<usize> {
+    while let Some(module) = state.get(position) {
+        match module.symbol {
+            Symbol::Push => position = brackets[position],
+            Symbol::Pop => return Some(position),
+            _ => {}
+        }
+        position += 1;
+    }
+    None
+}
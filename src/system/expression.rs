@@ -0,0 +1,452 @@
+// Arithmetic expressions used by parametric productions.
+//
+// Expression  Meaning
+// -------------------------------------------------------------------------
+//  1.5, x      Numbers and formal parameters
+//  + - * / %   Arithmetic
+//  ^           Exponentiation (right associative)
+//  < <= > >=   Comparisons, evaluating to 1 or 0
+//  == !=
+//  && || !     Logical operators, any non-zero value is true
+//  f(a, ...)   sqrt, abs, floor, ceil, sin, cos, tan (radians), min, max
+//
+// Formal parameters are resolved to slots while parsing, so evaluation is a
+// simple walk over the tree with the bound values of a production.
+
+use core::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Operator {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Pow,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    Equal,
+    NotEqual,
+    And,
+    Or,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Function {
+    Sqrt,
+    Abs,
+    Floor,
+    Ceil,
+    Sin,
+    Cos,
+    Tan,
+    Min,
+    Max,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expression {
+    Number(f32),
+    Parameter(usize),
+    Negate(Box<Expression>),
+    Not(Box<Expression>),
+    Binary(Operator, Box<Expression>, Box<Expression>),
+    Call(Function, Vec<Expression>),
+}
+
+impl Expression {
+    /// Parses `text`, resolving identifiers against `parameters`.
+    pub fn parse(text: &str, parameters: &[&str]) -> Result<Self, String> {
+        let mut parser = Parser {
+            text,
+            position: 0,
+            parameters,
+        };
+        let expression = parser.or()?;
+        parser.skip_whitespace();
+        match parser.position == text.len() {
+            true => Ok(expression),
+            false => Err(format!(
+                "Unexpected `{}` in expression `{}`.",
+                &text[parser.position..],
+                text
+            )),
+        }
+    }
+
+    /// Whether `text` parses as an expression, whatever names it uses.
+    pub fn is_well_formed(text: &str) -> bool {
+        let names: Vec<_> = text
+            .split(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '.'))
+            .filter(|word| word.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_'))
+            .filter(|word| Function::from_name(word).is_none())
+            .collect();
+        Self::parse(text, &names).is_ok()
+    }
+
+    pub fn eval(&self, values: &[f32]) -> f32 {
+        match self {
+            Self::Number(n) => *n,
+            Self::Parameter(slot) => values.get(*slot).copied().unwrap_or(0.0),
+            Self::Negate(e) => -e.eval(values),
+            Self::Not(e) => truth(e.eval(values) == 0.0),
+            Self::Binary(operator, a, b) => {
+                let a = a.eval(values);
+                // Short-circuit the logical operators.
+                match operator {
+                    Operator::And if a == 0.0 => return 0.0,
+                    Operator::Or if a != 0.0 => return 1.0,
+                    _ => {}
+                }
+                let b = b.eval(values);
+                match operator {
+                    Operator::Add => a + b,
+                    Operator::Sub => a - b,
+                    Operator::Mul => a * b,
+                    Operator::Div => a / b,
+                    Operator::Rem => a % b,
+                    Operator::Pow => a.powf(b),
+                    Operator::Less => truth(a < b),
+                    Operator::LessEqual => truth(a <= b),
+                    Operator::Greater => truth(a > b),
+                    Operator::GreaterEqual => truth(a >= b),
+                    Operator::Equal => truth(a == b),
+                    Operator::NotEqual => truth(a != b),
+                    Operator::And | Operator::Or => truth(b != 0.0),
+                }
+            }
+            Self::Call(function, args) => {
+                let arg = |i: usize| args.get(i).map_or(0.0, |e| e.eval(values));
+                match function {
+                    Function::Sqrt => arg(0).sqrt(),
+                    Function::Abs => arg(0).abs(),
+                    Function::Floor => arg(0).floor(),
+                    Function::Ceil => arg(0).ceil(),
+                    Function::Sin => arg(0).sin(),
+                    Function::Cos => arg(0).cos(),
+                    Function::Tan => arg(0).tan(),
+                    Function::Min => arg(0).min(arg(1)),
+                    Function::Max => arg(0).max(arg(1)),
+                }
+            }
+        }
+    }
+
+    /// Formats the expression back to text, naming the slots by `parameters`.
+    pub fn display<'a>(&'a self, parameters: &'a [&'a str]) -> impl fmt::Display + 'a {
+        Display {
+            expression: self,
+            parameters,
+        }
+    }
+}
+
+fn truth(value: bool) -> f32 {
+    if value {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+impl Operator {
+    fn precedence(&self) -> u8 {
+        match self {
+            Self::Or => 1,
+            Self::And => 2,
+            Self::Less
+            | Self::LessEqual
+            | Self::Greater
+            | Self::GreaterEqual
+            | Self::Equal
+            | Self::NotEqual => 3,
+            Self::Add | Self::Sub => 4,
+            Self::Mul | Self::Div | Self::Rem => 5,
+            Self::Pow => 7,
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Add => "+",
+            Self::Sub => "-",
+            Self::Mul => "*",
+            Self::Div => "/",
+            Self::Rem => "%",
+            Self::Pow => "^",
+            Self::Less => "<",
+            Self::LessEqual => "<=",
+            Self::Greater => ">",
+            Self::GreaterEqual => ">=",
+            Self::Equal => "==",
+            Self::NotEqual => "!=",
+            Self::And => "&&",
+            Self::Or => "||",
+        }
+    }
+}
+
+impl Function {
+    fn from_name(name: &str) -> Option<(Self, usize)> {
+        match name {
+            "sqrt" => Some((Self::Sqrt, 1)),
+            "abs" => Some((Self::Abs, 1)),
+            "floor" => Some((Self::Floor, 1)),
+            "ceil" => Some((Self::Ceil, 1)),
+            "sin" => Some((Self::Sin, 1)),
+            "cos" => Some((Self::Cos, 1)),
+            "tan" => Some((Self::Tan, 1)),
+            "min" => Some((Self::Min, 2)),
+            "max" => Some((Self::Max, 2)),
+            _ => None,
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Sqrt => "sqrt",
+            Self::Abs => "abs",
+            Self::Floor => "floor",
+            Self::Ceil => "ceil",
+            Self::Sin => "sin",
+            Self::Cos => "cos",
+            Self::Tan => "tan",
+            Self::Min => "min",
+            Self::Max => "max",
+        }
+    }
+}
+
+struct Display<'a> {
+    expression: &'a Expression,
+    parameters: &'a [&'a str],
+}
+
+impl Expression {
+    // How tightly the expression binds when written without parentheses,
+    // unary operators sitting between products and powers.
+    fn precedence(&self) -> u8 {
+        match self {
+            Self::Binary(operator, ..) => operator.precedence(),
+            Self::Negate(_) | Self::Not(_) => 6,
+            Self::Number(n) if n.is_sign_negative() => 6,
+            _ => 8,
+        }
+    }
+}
+
+// Parenthesizes only where the parser would otherwise group differently:
+// operators are left associative except `^`, whose exponent may be unary.
+impl fmt::Display for Display<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sub = |expression: &'_ Expression, parenthesize: bool| {
+            let display = Display {
+                expression,
+                parameters: self.parameters,
+            };
+            match parenthesize {
+                true => format!("({})", display),
+                false => display.to_string(),
+            }
+        };
+        match self.expression {
+            Expression::Number(n) => write!(f, "{}", n),
+            Expression::Parameter(slot) => match self.parameters.get(*slot) {
+                Some(name) => write!(f, "{}", name),
+                None => write!(f, "${}", slot),
+            },
+            Expression::Negate(e) => write!(f, "-{}", sub(e, e.precedence() < 6)),
+            Expression::Not(e) => write!(f, "!{}", sub(e, e.precedence() < 6)),
+            Expression::Binary(operator, a, b) => {
+                let precedence = operator.precedence();
+                let (left, right) = match operator {
+                    Operator::Pow => (a.precedence() <= precedence, b.precedence() < 6),
+                    _ => (a.precedence() < precedence, b.precedence() <= precedence),
+                };
+                write!(
+                    f,
+                    "{} {} {}",
+                    sub(a, left),
+                    operator.as_str(),
+                    sub(b, right)
+                )
+            }
+            Expression::Call(function, args) => {
+                write!(f, "{}(", function.as_str())?;
+                for (i, arg) in args.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", sub(arg, false))?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    position: usize,
+    parameters: &'a [&'a str],
+}
+
+impl<'a> Parser<'a> {
+    fn skip_whitespace(&mut self) {
+        let rest = &self.text[self.position..];
+        self.position += rest.len() - rest.trim_start().len();
+    }
+
+    fn peek(&mut self) -> &'a str {
+        self.skip_whitespace();
+        &self.text[self.position..]
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        if self.peek().starts_with(token) {
+            self.position += token.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn binary(
+        &mut self,
+        operators: &[(&str, Operator)],
+        operand: fn(&mut Self) -> Result<Expression, String>,
+    ) -> Result<Expression, String> {
+        let mut left = operand(self)?;
+        'outer: loop {
+            for (token, operator) in operators {
+                if self.eat(token) {
+                    let right = operand(self)?;
+                    left = Expression::Binary(*operator, Box::new(left), Box::new(right));
+                    continue 'outer;
+                }
+            }
+            return Ok(left);
+        }
+    }
+
+    fn or(&mut self) -> Result<Expression, String> {
+        self.binary(&[("||", Operator::Or)], Self::and)
+    }
+
+    fn and(&mut self) -> Result<Expression, String> {
+        self.binary(&[("&&", Operator::And)], Self::comparison)
+    }
+
+    fn comparison(&mut self) -> Result<Expression, String> {
+        self.binary(
+            &[
+                ("<=", Operator::LessEqual),
+                (">=", Operator::GreaterEqual),
+                ("==", Operator::Equal),
+                ("!=", Operator::NotEqual),
+                ("<", Operator::Less),
+                (">", Operator::Greater),
+            ],
+            Self::sum,
+        )
+    }
+
+    fn sum(&mut self) -> Result<Expression, String> {
+        self.binary(&[("+", Operator::Add), ("-", Operator::Sub)], Self::product)
+    }
+
+    fn product(&mut self) -> Result<Expression, String> {
+        self.binary(
+            &[
+                ("*", Operator::Mul),
+                ("/", Operator::Div),
+                ("%", Operator::Rem),
+            ],
+            Self::unary,
+        )
+    }
+
+    fn unary(&mut self) -> Result<Expression, String> {
+        if self.eat("-") {
+            // Fold negative literals, so that `-2` reads back as written.
+            match self.unary()? {
+                Expression::Number(n) => Ok(Expression::Number(-n)),
+                e => Ok(Expression::Negate(Box::new(e))),
+            }
+        } else if self.peek().starts_with("!=") {
+            Err(format!("Unexpected `!=` in expression `{}`.", self.text))
+        } else if self.eat("!") {
+            Ok(Expression::Not(Box::new(self.unary()?)))
+        } else {
+            self.power()
+        }
+    }
+
+    fn power(&mut self) -> Result<Expression, String> {
+        let base = self.primary()?;
+        if self.eat("^") {
+            let exponent = self.unary()?;
+            return Ok(Expression::Binary(
+                Operator::Pow,
+                Box::new(base),
+                Box::new(exponent),
+            ));
+        }
+        Ok(base)
+    }
+
+    fn primary(&mut self) -> Result<Expression, String> {
+        if self.eat("(") {
+            let expression = self.or()?;
+            return match self.eat(")") {
+                true => Ok(expression),
+                false => Err(format!("Missing `)` in expression `{}`.", self.text)),
+            };
+        }
+
+        let rest = self.peek();
+        let length = rest
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '.'))
+            .unwrap_or(rest.len());
+        let token = &rest[..length];
+        if token.is_empty() {
+            return Err(format!("Expected a value in expression `{}`.", self.text));
+        }
+        self.position += length;
+
+        if token.starts_with(|c: char| c.is_ascii_digit() || c == '.') {
+            return token
+                .parse()
+                .map(Expression::Number)
+                .map_err(|_| format!("Invalid number `{}`.", token));
+        }
+
+        if let Some(slot) = self.parameters.iter().position(|p| *p == token) {
+            return Ok(Expression::Parameter(slot));
+        }
+
+        match Function::from_name(token) {
+            Some((function, arity)) if self.eat("(") => {
+                let mut args = vec![self.or()?];
+                while self.eat(",") {
+                    args.push(self.or()?);
+                }
+                if !self.eat(")") {
+                    return Err(format!("Missing `)` in expression `{}`.", self.text));
+                }
+                match args.len() == arity {
+                    true => Ok(Expression::Call(function, args)),
+                    false => Err(format!(
+                        "`{}` takes {} argument(s), got {}.",
+                        token,
+                        arity,
+                        args.len()
+                    )),
+                }
+            }
+            _ => Err(format!("Unknown parameter `{}`.", token)),
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use crate::system::{Module, State, System};
+use std::collections::BTreeMap;
+use std::mem;
+
+/// The generations of a system, cached within a memory budget. Evicted
+/// generations are expanded again from the closest earlier one still cached,
+/// the axiom always being kept.
+pub struct History {
+    system: System,
+    // Each generation with the tick it was last used at.
+    cache: BTreeMap<usize, (State, u64)>,
+    budget: usize,
+    used: usize,
+    tick: u64,
+}
+
+impl History {
+    /// `budget` is in bytes. The generation last asked for is kept even
+    /// when it doesn't fit.
+    pub fn new(system: System, budget: usize) -> Self {
+        let start = system.start().clone();
+        let mut history = Self {
+            system,
+            cache: BTreeMap::new(),
+            budget,
+            used: 0,
+            tick: 0,
+        };
+        history.insert(0, start);
+        history
+    }
+
+    pub fn system(&self) -> &System {
+        &self.system
+    }
+
+    /// The bytes taken by the cached generations, roughly.
+    pub fn used(&self) -> usize {
+        self.used
+    }
+
+    pub fn is_cached(&self, generation: usize) -> bool {
+        self.cache.contains_key(&generation)
+    }
+
+    pub fn get(&mut self, generation: usize) -> &State {
+        self.tick += 1;
+        if let Some((_, used)) = self.cache.get_mut(&generation) {
+            *used = self.tick;
+        } else {
+            let (&from, (state, _)) = self.cache.range(..generation).next_back().unwrap();
+            let mut iterator = self.system.clone().resume(state.clone(), from as u64);
+            for current in from + 1..=generation {
+                iterator.next();
+                self.insert(current, iterator.state().clone());
+                self.evict(current);
+            }
+        }
+        self.evict(generation);
+        &self.cache[&generation].0
+    }
+
+    fn insert(&mut self, generation: usize, state: State) {
+        self.used += Self::size(&state);
+        self.cache.insert(generation, (state, self.tick));
+    }
+
+    // Drops the least recently used generations until the cache fits,
+    // sparing the axiom and `keep`.
+    fn evict(&mut self, keep: usize) {
+        while self.used > self.budget {
+            let oldest = self
+                .cache
+                .iter()
+                .filter(|(&g, _)| g != 0 && g != keep)
+                .min_by_key(|(&g, &(_, used))| (used, g))
+                .map(|(&g, _)| g);
+            match oldest {
+                Some(generation) => {
+                    let (state, _) = self.cache.remove(&generation).unwrap();
+                    self.used -= Self::size(&state);
+                }
+                None => break,
+            }
+        }
+    }
+
+    fn size(state: &State) -> usize {
+        state.capacity() * mem::size_of::<Module>()
+            + state
+                .iter()
+                .map(|m| m.params.capacity() * mem::size_of::<f32>())
+                .sum::<usize>()
+    }
+}
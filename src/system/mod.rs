@@ -1,17 +1,33 @@
+pub mod compact;
+pub mod context;
+pub mod expression;
+pub mod history;
+pub mod module;
+mod parallel;
+pub mod production;
+pub mod random;
+pub mod stream;
 pub mod symbol;
 
-use crate::symbol;
+pub use compact::Compact;
+pub use expression::Expression;
+pub use history::History;
+pub use module::Module;
+use parallel::Part;
+pub use production::{Pattern, Production, Template};
+use std::cell::OnceCell;
 use std::collections::HashMap;
 use std::mem;
+pub use stream::Stream;
 pub use symbol::Symbol;
 
-pub type State = Vec<Symbol>;
-pub type Rules = HashMap<Symbol, State>;
+pub type State = Vec<Module>;
+pub type Rules = HashMap<Symbol, Vec<Production>>;
 
 pub fn dump(state: &State) -> String {
     let mut string = String::new();
-    for symbol in state {
-        string.push_str(&format!("{}", symbol));
+    for module in state {
+        string.push_str(&format!("{}", module));
     }
     string
 }
@@ -20,20 +36,97 @@ pub fn dump(state: &State) -> String {
 pub struct System {
     rules: Rules,
     start: State,
+    seed: u64,
+    ignore: State,
+    threads: usize,
+    births: bool,
 }
 
 impl System {
-    pub fn new(mut rules: Rules, start: State) -> Self {
-        for constant in vec![symbol!('['), symbol!(']'), symbol!('+'), symbol!('-')] {
-            rules
-                .entry(constant.clone())
-                .or_insert_with(|| vec![constant]);
-        }
+    pub fn new(rules: HashMap<Symbol, State>, start: State) -> Self {
+        let rules = rules
+            .into_iter()
+            .map(|(symbol, state)| (symbol, vec![Production::new(state)]))
+            .collect();
+        Self::stochastic(rules, start, 0)
+    }
+
+    pub fn stochastic(rules: Rules, start: State, seed: u64) -> Self {
         Self {
             rules,
-            start: start.to_vec(),
+            start,
+            seed,
+            ignore: State::new(),
+            threads: 1,
+            births: false,
         }
     }
+
+    pub fn rules(&self) -> &Rules {
+        &self.rules
+    }
+
+    pub fn start(&self) -> &State {
+        &self.start
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    pub fn ignore(&self) -> &State {
+        &self.ignore
+    }
+
+    /// Symbols skipped when matching the context of a production.
+    pub fn with_ignore(mut self, ignore: State) -> Self {
+        self.ignore = ignore;
+        self
+    }
+
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    /// Rewrites every generation on up to `threads` threads, for large
+    /// states. The generations are the same whatever the number of threads.
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    pub fn births(&self) -> bool {
+        self.births
+    }
+
+    /// Tags the modules of every generation with their [`birth`](Module::birth).
+    /// Ids don't keep births, so a system that could be compacted is then
+    /// rewritten module by module. Depth-first streams always tag their
+    /// modules.
+    pub fn with_births(mut self, births: bool) -> Self {
+        self.births = births;
+        self
+    }
+
+    /// The modules of `generation`, expanded depth-first from the axiom
+    /// rather than held in memory all at once.
+    pub fn stream(self, generation: usize) -> Stream {
+        Stream::new(self, generation)
+    }
+
+    /// Iterates on from `state`, taken to be the given generation of this
+    /// system. Stochastic choices are the same as when iterating from the
+    /// start.
+    pub fn resume(self, state: State, generation: u64) -> SystemIterator {
+        let mut iterator = SystemIterator::new(state, self);
+        iterator.generation = generation;
+        iterator
+    }
 }
 
 impl IntoIterator for System {
@@ -42,7 +135,7 @@ impl IntoIterator for System {
     type IntoIter = SystemIterator;
 
     fn into_iter(self) -> Self::IntoIter {
-        SystemIterator::new(self.start, self.rules)
+        SystemIterator::new(self.start.clone(), self)
     }
 }
 
@@ -50,19 +143,132 @@ pub struct SystemIterator {
     state: State,
     rules: Rules,
     buffer: State,
+    seed: u64,
+    generation: u64,
+    ignore: State,
+    threads: usize,
+    context_sensitive: bool,
+    // Set when the system can be compacted, `state` is then unused.
+    compact: Option<Compacted>,
+}
+
+// The state of a compacted system as ids, decoded into modules only when
+// they are asked for.
+struct Compacted {
+    compact: Compact,
+    ids: Vec<u16>,
+    buffer: Vec<u16>,
+    decoded: OnceCell<State>,
 }
 
 impl SystemIterator {
-    fn new(state: State, rules: Rules) -> Self {
+    fn new(state: State, system: System) -> Self {
+        let System {
+            rules,
+            seed,
+            ignore,
+            threads,
+            births,
+            ..
+        } = system;
+        let context_sensitive = rules
+            .values()
+            .flatten()
+            .any(Production::is_context_sensitive);
+        let compact = Compact::new(&rules).filter(|_| !births);
+        let compact = compact.and_then(|mut compact| {
+            let ids = compact.encode(&state)?;
+            Some(Compacted {
+                compact,
+                ids,
+                buffer: Vec::new(),
+                decoded: OnceCell::new(),
+            })
+        });
         Self {
             state: state.clone(),
             rules,
             buffer: state,
+            seed,
+            generation: 0,
+            ignore,
+            threads,
+            context_sensitive,
+            compact,
         }
     }
 
     pub fn state(&self) -> &State {
-        &self.state
+        match &self.compact {
+            Some(c) => c.decoded.get_or_init(|| c.compact.decode(&c.ids)),
+            None => &self.state,
+        }
+    }
+
+    /// The generation of `state()`.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    // Rewrites the state, leaving the previous one in `buffer`.
+    fn step(&mut self) {
+        if let Some(c) = &mut self.compact {
+            c.decoded.take();
+            c.compact.rewrite_parallel(
+                &c.ids,
+                self.seed,
+                self.generation,
+                self.threads,
+                &mut c.buffer,
+            );
+            mem::swap(&mut c.ids, &mut c.buffer);
+            self.generation += 1;
+            return;
+        }
+
+        self.buffer.clear();
+        let brackets = match self.context_sensitive {
+            true => context::matching_brackets(&self.state),
+            false => Vec::new(),
+        };
+        let choose = |index: usize| {
+            let sample = random::uniform(self.seed, self.generation, index as u64);
+            choose(
+                &self.rules,
+                &self.state,
+                &brackets,
+                index,
+                &self.ignore,
+                sample,
+            )
+        };
+        let birth = self.generation + 1;
+        let chunks = parallel::chunks(self.state.len(), self.threads);
+        match chunks.len() {
+            0 | 1 => {
+                for (index, module) in self.state.iter().enumerate() {
+                    expand(module, choose(index), birth, &mut self.buffer);
+                }
+            }
+            // The productions of every chunk are chosen first, which counts
+            // its successors, so that each is written into its own part of
+            // the buffer.
+            _ => parallel::rewrite(
+                &chunks,
+                |chunk| {
+                    let chosen: Vec<_> = chunk.clone().map(choose).collect();
+                    (chosen.iter().map(length).sum(), (chunk, chosen))
+                },
+                |(chunk, chosen), part| {
+                    for (index, chosen) in chunk.zip(chosen) {
+                        write(&self.state[index], chosen, birth, part);
+                    }
+                },
+                &mut self.buffer,
+            ),
+        }
+        self.generation += 1;
+        mem::swap(&mut self.state, &mut self.buffer);
     }
 }
 
@@ -70,14 +276,76 @@ impl Iterator for SystemIterator {
     type Item = State;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.buffer.clear();
-        for character in &self.state {
-            match self.rules.get(&character) {
-                Some(replacement) => self.buffer.append(&mut replacement.clone()),
-                None => self.buffer.push(character.clone()),
-            }
+        if let Some(c) = &mut self.compact {
+            let previous = match c.decoded.take() {
+                Some(state) => state,
+                None => c.compact.decode(&c.ids),
+            };
+            self.step();
+            return Some(previous);
         }
-        mem::swap(&mut self.state, &mut self.buffer);
+        self.step();
         Some(mem::take(&mut self.buffer))
     }
+
+    // Skipped generations are never decoded.
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        for _ in 0..n {
+            self.step();
+        }
+        self.next()
+    }
+}
+
+// The production a module is rewritten with and the values bound to its
+// parameters, `None` when the module is kept as it is.
+type Chosen<'a> = Option<(&'a Production, Vec<f32>)>;
+
+// Chooses how the module at `index` of `state` is rewritten, `sample`
+// choosing among stochastic productions.
+fn choose<'a>(
+    rules: &'a Rules,
+    state: &[Module],
+    brackets: &[usize],
+    index: usize,
+    ignore: &State,
+    sample: f64,
+) -> Chosen<'a> {
+    let productions = rules.get(&state[index].symbol)?;
+    let bind = |p: &Production| p.bind(state, brackets, index, ignore);
+    let production = production::select(productions, sample, |p| bind(p).is_some())?;
+    Some((production, bind(production).unwrap_or_default()))
+}
+
+fn length(chosen: &Chosen) -> usize {
+    chosen
+        .as_ref()
+        .map_or(1, |(production, _)| production.successor.len())
+}
+
+// Appends the successor of `module` to `output`. Modules created by a
+// production are born in `generation`, a module kept as it is keeps its birth.
+fn expand(module: &Module, chosen: Chosen, generation: u64, output: &mut State) {
+    let start = output.len();
+    match chosen {
+        Some((production, values)) => production.expand(&values, output),
+        None => return output.push(module.clone()),
+    }
+    for successor in &mut output[start..] {
+        successor.birth = generation as u32;
+    }
+}
+
+// Writes the successor of `module` into `output`, as `expand` appends it.
+fn write(module: &Module, chosen: Chosen, generation: u64, output: &mut Part<Module>) {
+    match chosen {
+        Some((production, values)) => {
+            for template in &production.successor {
+                let mut successor = template.instantiate(&values);
+                successor.birth = generation as u32;
+                output.push(successor);
+            }
+        }
+        None => output.push(module.clone()),
+    }
 }
@@ -0,0 +1,63 @@
+use crate::system::Symbol;
+use core::fmt;
+
+/// A symbol together with its actual parameters, e.g. `F(1.5)`.
+///
+/// `birth` is the generation the module was created in, as tagged by
+/// [`SystemIterator`](crate::system::SystemIterator). It isn't part of what
+/// the module is, and modules compare equal whatever their birth.
+#[derive(Debug, Clone)]
+pub struct Module {
+    pub symbol: Symbol,
+    pub params: Vec<f32>,
+    pub birth: u32,
+}
+
+impl Module {
+    pub fn new(symbol: Symbol, params: Vec<f32>) -> Self {
+        Self {
+            symbol,
+            params,
+            birth: 0,
+        }
+    }
+
+    pub fn param(&self, index: usize) -> Option<f32> {
+        self.params.get(index).copied()
+    }
+}
+
+impl PartialEq for Module {
+    fn eq(&self, other: &Self) -> bool {
+        self.symbol == other.symbol && self.params == other.params
+    }
+}
+
+impl From<Symbol> for Module {
+    fn from(symbol: Symbol) -> Self {
+        Self::new(symbol, Vec::new())
+    }
+}
+
+impl From<char> for Module {
+    fn from(c: char) -> Self {
+        Self::from(Symbol::from(c))
+    }
+}
+
+impl fmt::Display for Module {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.symbol)?;
+        if !self.params.is_empty() {
+            write!(f, "(")?;
+            for (i, param) in self.params.iter().enumerate() {
+                if i > 0 {
+                    write!(f, ",")?;
+                }
+                write!(f, "{}", param)?;
+            }
+            write!(f, ")")?;
+        }
+        Ok(())
+    }
+}
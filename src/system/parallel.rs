@@ -0,0 +1,89 @@
+// Rewriting a generation on several threads. Each module is rewritten from
+// the previous generation alone, so the state can be cut into contiguous
+// chunks, rewritten independently, and the successors put back in order.
+
+use std::mem::MaybeUninit;
+use std::ops::Range;
+use std::thread;
+
+// Below this many modules per thread, spawning costs more than it saves.
+const MIN_CHUNK: usize = 4096;
+
+/// Splits `0..len` into at most `threads` contiguous ranges.
+pub fn chunks(len: usize, threads: usize) -> Vec<Range<usize>> {
+    let size = len.div_ceil(threads.max(1)).max(MIN_CHUNK);
+    (0..len)
+        .step_by(size)
+        .map(|start| start..(start + size).min(len))
+        .collect()
+}
+
+/// `f` of every chunk, each on its own thread, in the order of `chunks`.
+pub fn map<T: Send>(chunks: &[Range<usize>], f: impl Fn(Range<usize>) -> T + Sync) -> Vec<T> {
+    let f = &f;
+    thread::scope(|scope| {
+        let handles: Vec<_> = chunks
+            .iter()
+            .map(|chunk| scope.spawn(move || f(chunk.clone())))
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    })
+}
+
+/// The slots of the output one chunk writes its successors into, in order.
+pub struct Part<'a, T> {
+    slots: &'a mut [MaybeUninit<T>],
+    written: usize,
+}
+
+impl<T> Part<'_, T> {
+    /// Panics when the part is already full.
+    pub fn push(&mut self, value: T) {
+        self.slots[self.written].write(value);
+        self.written += 1;
+    }
+}
+
+impl<T: Copy> Part<'_, T> {
+    pub fn extend_from_slice(&mut self, values: &[T]) {
+        values.iter().for_each(|&value| self.push(value));
+    }
+}
+
+/// Replaces `output` with the successors of `chunks`. `plan` gives the length
+/// of the successor of a chunk along with what `write` needs to write it,
+/// so that nothing is worked out twice, and `write` must fill its part
+/// exactly. Both run on a thread per chunk.
+pub fn rewrite<T: Send, P: Send>(
+    chunks: &[Range<usize>],
+    plan: impl Fn(Range<usize>) -> (usize, P) + Sync,
+    write: impl Fn(P, &mut Part<T>) + Sync,
+    output: &mut Vec<T>,
+) {
+    let plans = map(chunks, plan);
+    let len = plans.iter().map(|(length, _)| length).sum();
+    output.clear();
+    output.reserve(len);
+
+    // The prefix sum of the lengths places every successor in `output`.
+    let mut parts = Vec::with_capacity(chunks.len());
+    let mut rest = &mut output.spare_capacity_mut()[..len];
+    for (length, plan) in plans {
+        let (slots, tail) = rest.split_at_mut(length);
+        parts.push((Part { slots, written: 0 }, plan));
+        rest = tail;
+    }
+
+    let write = &write;
+    thread::scope(|scope| {
+        for (mut part, plan) in parts {
+            scope.spawn(move || {
+                write(plan, &mut part);
+                assert_eq!(part.written, part.slots.len(), "A part was left unfilled.");
+            });
+        }
+    });
+    // SAFETY: every part was filled, or a thread panicked, and the parts
+    // cover the first `len` slots.
+    unsafe { output.set_len(len) };
+}
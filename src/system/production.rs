@@ -0,0 +1,199 @@
+use crate::system::{context, Expression, Module, State, Symbol};
+
+/// A module of a predecessor or context, with its formal parameters.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pattern {
+    pub symbol: Symbol,
+    pub params: Vec<String>,
+}
+
+impl Pattern {
+    pub fn is_bracket(&self) -> bool {
+        matches!(self.symbol, Symbol::Push | Symbol::Pop)
+    }
+
+    /// A pattern without formal parameters matches the symbol with any
+    /// number of actual parameters.
+    pub fn matches(&self, module: &Module) -> bool {
+        self.symbol == module.symbol
+            && (self.params.is_empty() || self.params.len() == module.params.len())
+    }
+}
+
+impl From<Module> for Pattern {
+    fn from(module: Module) -> Self {
+        Self {
+            symbol: module.symbol,
+            params: Vec::new(),
+        }
+    }
+}
+
+/// A module of a successor, its parameters given as expressions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    pub symbol: Symbol,
+    pub args: Vec<Expression>,
+}
+
+impl Template {
+    pub fn instantiate(&self, values: &[f32]) -> Module {
+        Module::new(
+            self.symbol,
+            self.args.iter().map(|arg| arg.eval(values)).collect(),
+        )
+    }
+}
+
+impl From<Module> for Template {
+    fn from(module: Module) -> Self {
+        Self {
+            symbol: module.symbol,
+            args: module.params.into_iter().map(Expression::Number).collect(),
+        }
+    }
+}
+
+// A production `left < predecessor(params) > right : condition -> successor`.
+// The formal parameters are bound to slots in the order they are written:
+// those of the left context, of the predecessor, and of the right context.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Production {
+    pub left: Vec<Pattern>,
+    pub params: Vec<String>,
+    pub right: Vec<Pattern>,
+    pub condition: Option<Expression>,
+    pub successor: Vec<Template>,
+    pub weight: f32,
+}
+
+impl Production {
+    pub fn new(successor: State) -> Self {
+        Self::weighted(successor, 1.0)
+    }
+
+    pub fn weighted(successor: State, weight: f32) -> Self {
+        Self {
+            left: Vec::new(),
+            params: Vec::new(),
+            right: Vec::new(),
+            condition: None,
+            successor: successor.into_iter().map(Template::from).collect(),
+            weight,
+        }
+    }
+
+    pub fn with_context(mut self, left: State, right: State) -> Self {
+        self.left = left.into_iter().map(Pattern::from).collect();
+        self.right = right.into_iter().map(Pattern::from).collect();
+        self
+    }
+
+    /// The names of all formal parameters, indexed by slot.
+    pub fn parameters(&self) -> Vec<&str> {
+        self.left
+            .iter()
+            .flat_map(|p| &p.params)
+            .chain(&self.params)
+            .chain(self.right.iter().flat_map(|p| &p.params))
+            .map(String::as_str)
+            .collect()
+    }
+
+    pub fn is_context_sensitive(&self) -> bool {
+        !self.left.is_empty() || !self.right.is_empty()
+    }
+
+    pub(super) fn is_trivial(&self) -> bool {
+        !self.is_context_sensitive() && self.params.is_empty() && self.condition.is_none()
+    }
+
+    /// Binds the formal parameters of this production for the module at
+    /// `index`, returning `None` when a context or the condition doesn't hold.
+    /// `brackets` is the result of [`context::matching_brackets`].
+    pub fn bind(
+        &self,
+        state: &[Module],
+        brackets: &[usize],
+        index: usize,
+        ignore: &State,
+    ) -> Option<Vec<f32>> {
+        if self.is_trivial() {
+            return Some(Vec::new());
+        }
+        if !self.params.is_empty() && self.params.len() != state[index].params.len() {
+            return None;
+        }
+
+        let mut matched = Vec::new();
+        if !context::left_matches(&self.left, state, brackets, index, ignore, &mut matched) {
+            return None;
+        }
+        matched.reverse();
+        matched.push(index);
+        if !context::right_matches(&self.right, state, brackets, index, ignore, &mut matched) {
+            return None;
+        }
+
+        let arities = self
+            .left
+            .iter()
+            .map(|p| p.params.len())
+            .chain([self.params.len()])
+            .chain(
+                self.right
+                    .iter()
+                    .filter(|p| !p.is_bracket())
+                    .map(|p| p.params.len()),
+            );
+        let mut values = Vec::new();
+        for (position, arity) in matched.into_iter().zip(arities) {
+            values.extend_from_slice(&state[position].params[..arity]);
+        }
+        match &self.condition {
+            Some(condition) if condition.eval(&values) == 0.0 => None,
+            _ => Some(values),
+        }
+    }
+
+    pub fn expand(&self, values: &[f32], output: &mut State) {
+        output.extend(self.successor.iter().map(|t| t.instantiate(values)));
+    }
+}
+
+/// Picks one of the `productions` that `applies`, proportionally to its weight,
+/// `sample` being a number in `[0, 1)`. Applicable context-sensitive
+/// productions take precedence over context-free ones.
+pub fn select(
+    productions: &[Production],
+    sample: f64,
+    applies: impl Fn(&Production) -> bool,
+) -> Option<&Production> {
+    let contextual = productions
+        .iter()
+        .any(|p| p.is_context_sensitive() && applies(p));
+    let candidates = || {
+        productions
+            .iter()
+            .filter(|p| p.is_context_sensitive() == contextual && applies(p))
+    };
+
+    let index = pick(candidates().map(|p| p.weight), sample)?;
+    candidates().nth(index)
+}
+
+/// The index of one of `weights`, picked proportionally to it, `sample`
+/// being a number in `[0, 1)`.
+pub fn pick(weights: impl Iterator<Item = f32> + Clone, sample: f64) -> Option<usize> {
+    let total: f64 = weights.clone().map(|w| w as f64).sum();
+    let mut target = sample * total;
+    let mut chosen = None;
+    for (index, weight) in weights.enumerate() {
+        chosen = Some(index);
+        target -= weight as f64;
+        if target < 0.0 {
+            break;
+        }
+    }
+    chosen
+}
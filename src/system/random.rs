@@ -0,0 +1,22 @@
+// A stateless, counter based generator (SplitMix64 finalizer).
+//
+// Every random draw is a pure function of the seed, the generation being
+// produced and the index of the rewritten symbol, so expanding the same
+// system with the same seed always yields the same state, regardless of
+// how (or in which order) the symbols are rewritten.
+
+const GOLDEN_GAMMA: u64 = 0x9E37_79B9_7F4A_7C15;
+
+fn mix(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Returns a number in `[0, 1)` for the symbol at `index` of `generation`.
+pub fn uniform(seed: u64, generation: u64, index: u64) -> f64 {
+    let key = mix(seed.wrapping_add(GOLDEN_GAMMA));
+    let key = mix(key ^ generation.wrapping_mul(GOLDEN_GAMMA));
+    let key = mix(key ^ index.wrapping_add(GOLDEN_GAMMA));
+    (key >> 11) as f64 / (1u64 << 53) as f64
+}
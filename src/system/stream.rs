@@ -0,0 +1,89 @@
+use crate::system::{choose, expand, random, Module, Production, Rules, State, System};
+
+/// The modules of one generation of a system, yielded in order without the
+/// generation ever being held in memory.
+///
+/// Each module of the axiom is rewritten depth-first, down to the generation
+/// asked for, so that only one successor per generation is kept at a time.
+/// Every generation counts the modules it has rewritten so far, which gives
+/// stochastic productions the same choices as [`SystemIterator`].
+///
+/// Context needs the neighbours of a module, so the generations of a
+/// context-sensitive system are expanded as a whole instead, and so are those
+/// of a system with several [`threads`](System::with_threads), to rewrite
+/// them in parallel.
+///
+/// [`SystemIterator`]: crate::system::SystemIterator
+#[derive(Debug, Clone)]
+pub struct Stream {
+    rules: Rules,
+    seed: u64,
+    ignore: State,
+    generation: usize,
+    // The successors being walked, reversed, one per generation.
+    stack: Vec<State>,
+    // How many modules of each generation were rewritten.
+    counters: Vec<u64>,
+    depth_first: bool,
+}
+
+impl Stream {
+    pub(super) fn new(system: System, generation: usize) -> Self {
+        let context_sensitive = system
+            .rules
+            .values()
+            .flatten()
+            .any(Production::is_context_sensitive);
+        let whole = context_sensitive || system.threads > 1;
+        let mut start = match whole {
+            true => system.clone().into_iter().nth(generation).unwrap(),
+            false => system.start,
+        };
+        start.reverse();
+        Self {
+            rules: system.rules,
+            seed: system.seed,
+            ignore: system.ignore,
+            generation: match whole {
+                true => 0,
+                false => generation,
+            },
+            stack: vec![start],
+            counters: vec![0; generation],
+            depth_first: !whole,
+        }
+    }
+
+    /// Whether the modules are expanded depth-first, rather than taken from
+    /// the whole generation.
+    pub fn is_depth_first(&self) -> bool {
+        self.depth_first
+    }
+}
+
+impl Iterator for Stream {
+    type Item = Module;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let level = self.stack.len().checked_sub(1)?;
+            let Some(module) = self.stack[level].pop() else {
+                self.stack.pop();
+                continue;
+            };
+            if level == self.generation {
+                return Some(module);
+            }
+
+            let index = self.counters[level];
+            self.counters[level] += 1;
+            let sample = random::uniform(self.seed, level as u64, index);
+            let mut successor = State::new();
+            let module = [module];
+            let chosen = choose(&self.rules, &module, &[], 0, &self.ignore, sample);
+            expand(&module[0], chosen, level as u64 + 1, &mut successor);
+            successor.reverse();
+            self.stack.push(successor);
+        }
+    }
+}
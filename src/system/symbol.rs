@@ -14,6 +14,7 @@ use core::fmt;
 //    @             Draw a dot with line width radius
 //    {             Open a polygon
 //    }             Close a polygon and fill it with fill colour
+//    .             Record the position as a vertex of the open polygon
 //    >             Multiply the line length by the line length scale factor
 //    <             Divide the line length by the line length scale factor
 //    &             Swap the meaning of + and -
@@ -21,8 +22,21 @@ use core::fmt;
 //    )             Increment turning angle by turning angle increment
 //
 // source: http://www.paulbourke.net/fractals/lsys/
+//
+// In 3D (`dimensions = 3`) the turtle follows "The Algorithmic Beauty of
+// Plants" instead, turning its heading H, left L and up U vectors:
+//
+//    + -           Turn left / right around U
+//    & ^           Pitch down / up around L
+//    \ /           Roll left / right around H
+//    |             Turn around by 180 degrees around U
+//    $             Roll so that L is horizontal
+//
+// In both, lines, dots and polygons take their colour from the `[palette]`:
+//
+//    '             Advance the colour index, `'(n)` sets it to n
 
-#[derive(Debug, Eq, Hash, PartialEq, Clone)]
+#[derive(Debug, Eq, Hash, PartialEq, Clone, Copy)]
 pub enum Symbol {
     Var(char),
     Draw,
@@ -37,11 +51,17 @@ pub enum Symbol {
     Dot,
     OpenPolygon,
     ClosePolygon,
+    Vertex,
     MulLine,
     DivLine,
     SwapOperations,
     IncAngle,
     DecAngle,
+    PitchUp,
+    RollLeft,
+    RollRight,
+    Level,
+    NextColor,
 }
 
 impl From<char> for Symbol {
@@ -59,11 +79,17 @@ impl From<char> for Symbol {
             '@' => Self::Dot,
             '{' => Self::OpenPolygon,
             '}' => Self::ClosePolygon,
+            '.' => Self::Vertex,
             '>' => Self::MulLine,
             '<' => Self::DivLine,
             '&' => Self::SwapOperations,
-            '(' => Self::IncAngle,
-            ')' => Self::DecAngle,
+            '(' => Self::DecAngle,
+            ')' => Self::IncAngle,
+            '^' => Self::PitchUp,
+            '\\' => Self::RollLeft,
+            '/' => Self::RollRight,
+            '$' => Self::Level,
+            '\'' => Self::NextColor,
             c => Self::Var(c),
         }
     }
@@ -84,11 +110,17 @@ impl fmt::Display for Symbol {
             Self::Dot => '@',
             Self::OpenPolygon => '{',
             Self::ClosePolygon => '}',
+            Self::Vertex => '.',
             Self::MulLine => '>',
             Self::DivLine => '<',
             Self::SwapOperations => '&',
-            Self::IncAngle => '(',
-            Self::DecAngle => ')',
+            Self::DecAngle => '(',
+            Self::IncAngle => ')',
+            Self::PitchUp => '^',
+            Self::RollLeft => '\\',
+            Self::RollRight => '/',
+            Self::Level => '$',
+            Self::NextColor => '\'',
             Self::Var(c) => *c,
         };
         write!(f, "{}", c)
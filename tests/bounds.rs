@@ -0,0 +1,43 @@
+use l_system::graphics::turtle::{self, Point};
+use l_system::graphics::{Bounds, Config, Transform};
+use l_system::parser;
+
+fn bounds(source: &str) -> Option<Bounds> {
+    let config = Config::new(10, 1.0, 1.1, 90.0, 45.0);
+    Bounds::of(&turtle::interpret(
+        parser::parse_state(source).unwrap(),
+        &config,
+    ))
+}
+
+#[test]
+fn test_bounds() {
+    // Grows downwards and to the right, off a window anchored at the bottom.
+    let bounds = bounds("-F-F-F").unwrap();
+    let close = |a: Point, b: Point| (a - b).length() < 1e-4;
+    assert!(close(bounds.min, Point::xyz(-0.5, -10.5, -0.5)));
+    assert!(close(bounds.max, Point::xyz(10.5, 0.5, 0.5)));
+    assert!(close(bounds.center(), Point::new(5.0, -5.0)));
+
+    assert_eq!(self::bounds("f+f"), None);
+}
+
+#[test]
+fn test_fit() {
+    let bounds = bounds("-F-F-F").unwrap();
+    let fit = Transform::fit(&bounds, 220.0, 120.0, 10.0);
+
+    // Limited by the height: 100 pixels for 11 units.
+    assert!((fit.scale - 100.0 / 11.0).abs() < 1e-4);
+    let (x, y) = fit.apply(bounds.center());
+    assert!((x - 110.0).abs() < 1e-3 && (y - 60.0).abs() < 1e-3);
+    let (_, top) = fit.apply(bounds.max);
+    assert!((top - 10.0).abs() < 1e-3);
+
+    // A single dot has no extent to scale.
+    let dot = Bounds {
+        min: Point::default(),
+        max: Point::default(),
+    };
+    assert_eq!(Transform::fit(&dot, 100.0, 100.0, 0.0).scale, 1.0);
+}
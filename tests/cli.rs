@@ -0,0 +1,236 @@
+use l_system::parser;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+fn l_system(args: &[&str]) -> (Option<i32>, String, String) {
+    let output = Command::new(env!("CARGO_BIN_EXE_l_system"))
+        .args(args)
+        .output()
+        .unwrap();
+    (
+        output.status.code(),
+        String::from_utf8_lossy(&output.stdout).into_owned(),
+        String::from_utf8_lossy(&output.stderr).into_owned(),
+    )
+}
+
+#[test]
+fn test_expand() {
+    let (code, stdout, _) = l_system(&["expand", "systems/branch.lsys", "-n", "1"]);
+
+    assert_eq!(code, Some(0));
+    assert_eq!(stdout.trim(), "F-[[X]+X]+F[+FX]-X");
+}
+
+#[test]
+fn test_expand_stdin() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_l_system"))
+        .args(["expand", "-", "-n", "2"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let source = "[config]\n\n[rules]\nF -> F+F\n\n[start]\naxiom = F\n";
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(source.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "F+F+F+F");
+}
+
+#[test]
+fn test_expand_threads() {
+    let path = "systems/stochastic.lsys";
+    let (code, sequential, _) = l_system(&["expand", path, "-n", "7"]);
+    let (_, parallel, _) = l_system(&["expand", path, "-n", "7", "-j", "4"]);
+
+    assert_eq!(code, Some(0));
+    assert!(sequential.len() > 4 * 4096);
+    assert_eq!(parallel, sequential);
+    // The CLI streams the generation; with threads it's rewritten as a whole.
+    let (_, system) = parser::parse(path).unwrap();
+    assert!(system.clone().stream(7).is_depth_first());
+    assert!(!system.with_threads(4).stream(7).is_depth_first());
+}
+
+#[test]
+fn test_validate() {
+    let (code, stdout, _) = l_system(&["validate", "systems/branch.lsys"]);
+    assert_eq!(code, Some(0));
+    assert!(stdout.contains("ok"));
+
+    let (code, _, stderr) = l_system(&["validate", "systems/missing.lsys"]);
+    assert_eq!(code, Some(1));
+    assert!(stderr.starts_with("error:"));
+}
+
+#[test]
+fn test_render_svg() {
+    let output = std::env::temp_dir().join("l_system_cli_render.svg");
+    let output = output.to_str().unwrap();
+    let (code, _, _) = l_system(&["render", "systems/branch.lsys", "-n", "3", "-o", output]);
+
+    assert_eq!(code, Some(0));
+    assert!(std::fs::read_to_string(output).unwrap().starts_with("<svg"));
+}
+
+#[test]
+fn test_render_png() {
+    let output = std::env::temp_dir().join("l_system_cli_render.png");
+    let output = output.to_str().unwrap();
+    let (code, _, _) = l_system(&[
+        "render",
+        "systems/branch.lsys",
+        "-n",
+        "3",
+        "-o",
+        output,
+        "--size",
+        "64x32",
+        "--background",
+        "#10203040",
+        "--supersampling",
+        "2",
+    ]);
+    let png = std::fs::read(output).unwrap();
+
+    assert_eq!(code, Some(0));
+    assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+    assert_eq!(&png[16..24], &[0, 0, 0, 64, 0, 0, 0, 32]);
+}
+
+#[test]
+fn test_render_gcode() {
+    let output = std::env::temp_dir().join("l_system_cli_render.gcode");
+    let output = output.to_str().unwrap();
+    let (code, _, _) = l_system(&[
+        "render",
+        "systems/branch.lsys",
+        "-n",
+        "2",
+        "-o",
+        output,
+        "--page",
+        "100x100",
+        "--feed",
+        "900",
+        "--pen-up",
+        "M3 S90",
+        "--pen-down",
+        "M3 S30",
+    ]);
+    let gcode = std::fs::read_to_string(output).unwrap();
+
+    assert_eq!(code, Some(0));
+    assert!(gcode.starts_with("; 100x100mm"));
+    assert!(gcode.contains("F900"));
+    assert!(gcode.contains("M3 S30"));
+}
+
+#[test]
+fn test_render_stl() {
+    let output = std::env::temp_dir().join("l_system_cli_render.stl");
+    let output = output.to_str().unwrap();
+    let (code, _, _) = l_system(&["render", "systems/branch.lsys", "-n", "1", "-o", output]);
+    let stl = std::fs::read(output).unwrap();
+
+    assert_eq!(code, Some(0));
+    let triangles = u32::from_le_bytes(stl[80..84].try_into().unwrap()) as usize;
+    assert_eq!(stl.len(), 84 + 50 * triangles);
+
+    let (code, _, _) = l_system(&[
+        "render",
+        "systems/branch.lsys",
+        "-n",
+        "1",
+        "-o",
+        output,
+        "--ascii",
+        "--sides",
+        "5",
+    ]);
+    assert_eq!(code, Some(0));
+    assert!(std::fs::read_to_string(output)
+        .unwrap()
+        .starts_with("solid"));
+}
+
+#[test]
+fn test_usage_errors() {
+    assert_eq!(l_system(&[]).0, Some(2));
+    assert_eq!(l_system(&["grow", "systems/branch.lsys"]).0, Some(2));
+    assert_eq!(
+        l_system(&["expand", "systems/branch.lsys", "-n"]).0,
+        Some(2)
+    );
+    assert_eq!(l_system(&["render", "systems/branch.lsys"]).0, Some(2));
+    assert_eq!(
+        l_system(&[
+            "render",
+            "systems/branch.lsys",
+            "-o",
+            "a.png",
+            "--size",
+            "64"
+        ])
+        .0,
+        Some(2)
+    );
+    assert_eq!(
+        l_system(&[
+            "render",
+            "systems/branch.lsys",
+            "-o",
+            "a.png",
+            "--background",
+            "red"
+        ])
+        .0,
+        Some(2)
+    );
+    assert_eq!(
+        l_system(&[
+            "render",
+            "systems/branch.lsys",
+            "-o",
+            "a.gcode",
+            "--page",
+            "0x10"
+        ])
+        .0,
+        Some(2)
+    );
+    let (code, _, stderr) = l_system(&[
+        "render",
+        "systems/branch.lsys",
+        "-o",
+        "a.png",
+        "--size",
+        "4000000000x4000000000",
+        "--supersampling",
+        "4000000000",
+    ]);
+    assert_eq!(code, Some(2));
+    assert!(stderr.contains("too large to render"));
+}
+
+#[test]
+fn test_help() {
+    let (code, stdout, _) = l_system(&["--help"]);
+    assert_eq!(code, Some(0));
+    assert!(stdout.contains("render"));
+    assert_eq!(
+        l_system(&["expand", "systems/branch.lsys", "-h"]).0,
+        Some(0)
+    );
+
+    // Only in flag position: here `-h` is the output file.
+    let (code, stdout, _) = l_system(&["expand", "systems/missing.lsys", "-o", "-h"]);
+    assert_eq!(code, Some(1));
+    assert!(stdout.is_empty());
+}
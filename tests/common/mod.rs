@@ -0,0 +1,54 @@
+// Helpers shared by the integration tests, each of which uses only some.
+#![allow(dead_code)]
+
+use l_system::graphics::turtle::{self, Point, Primitive};
+use l_system::graphics::{Config, Rgba};
+use l_system::parser;
+use l_system::system::System;
+
+/// Lines 10 long and right angles, `scale` being both the line width
+/// increment and the line length scale factor.
+pub fn config(scale: f32) -> Config {
+    Config::new(10, scale, scale as f64, 90.0, 45.0)
+}
+
+/// The primitives `source`, an axiom, draws.
+pub fn draw(config: &Config, source: &str) -> Vec<Primitive> {
+    turtle::interpret(parser::parse_state(source).unwrap(), config)
+}
+
+pub fn system(path: &str) -> System {
+    parser::parse(path).unwrap().1
+}
+
+/// The segments among `primitives`, as `(from, to, width, depth)`.
+pub fn segments(primitives: &[Primitive]) -> Vec<(Point, Point, f32, usize)> {
+    primitives
+        .iter()
+        .filter_map(|p| match p {
+            Primitive::Segment {
+                from,
+                to,
+                width,
+                depth,
+                ..
+            } => Some((*from, *to, *width, *depth)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// The colour of every primitive, `None` where renderers pick their own.
+pub fn colors(primitives: &[Primitive]) -> Vec<Option<Rgba>> {
+    primitives
+        .iter()
+        .map(|p| match p {
+            Primitive::Segment { color, .. } | Primitive::Dot { color, .. } => *color,
+            Primitive::Polygon { color, .. } => Some(*color),
+        })
+        .collect()
+}
+
+pub fn assert_close(a: Point, b: Point) {
+    assert!((a - b).length() < 1e-4, "{:?} != {:?}", a, b);
+}
@@ -0,0 +1,50 @@
+mod common;
+
+use common::system;
+use l_system::system::Compact;
+
+#[test]
+fn test_compact_systems() {
+    for path in ["systems/branch.lsys", "systems/stochastic.lsys"] {
+        assert!(Compact::new(system(path).rules()).is_some(), "{}", path);
+    }
+    // Parameters and context need modules.
+    for path in ["systems/parametric.lsys", "systems/context.lsys"] {
+        assert!(Compact::new(system(path).rules()).is_none(), "{}", path);
+    }
+}
+
+#[test]
+fn test_compact_rewrite() {
+    let system = system("systems/stochastic.lsys");
+    let mut compact = Compact::new(system.rules()).unwrap();
+    let start = compact.encode(system.start()).unwrap();
+    assert_eq!(&compact.decode(&start), system.start());
+
+    // The same choices as rewriting module by module.
+    let (mut ids, mut buffer) = (start, Vec::new());
+    for generation in 0..5 {
+        buffer.clear();
+        compact.rewrite(&ids, system.seed(), generation, &mut buffer);
+        std::mem::swap(&mut ids, &mut buffer);
+    }
+    let streamed: Vec<_> = system.stream(5).collect();
+    assert_eq!(compact.decode(&ids), streamed);
+}
+
+#[test]
+fn test_compact_iteration() {
+    let system = system("systems/branch.lsys");
+    let mut iterator = system.clone().into_iter();
+    iterator.next();
+    assert_eq!(
+        iterator.state(),
+        &system.clone().into_iter().nth(1).unwrap()
+    );
+    // `nth` skips decoding, but not rewriting.
+    let mut stepped = system.clone().into_iter();
+    for _ in 0..4 {
+        stepped.next();
+    }
+    assert_eq!(system.into_iter().nth(4), stepped.next());
+}
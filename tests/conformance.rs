@@ -0,0 +1,134 @@
+// The turtle against the symbol table of `symbol.rs`, one symbol at a time.
+
+mod common;
+
+use common::{assert_close, config, draw};
+use l_system::graphics::turtle::{self, Point, Primitive};
+use l_system::parser;
+
+// The line length of `config`.
+const L: f32 = 10.0;
+
+fn segments(source: &str) -> Vec<(Point, Point, f32, usize)> {
+    common::segments(&draw(&config(2.0), source))
+}
+
+// Where the last segment drawn by `source` ends.
+fn end(source: &str) -> Point {
+    segments(source).last().unwrap().1
+}
+
+#[test]
+fn test_draw_and_move() {
+    assert_eq!(
+        segments("F")[0],
+        (Point::default(), Point::new(0.0, L), 1.0, 0)
+    );
+    assert_eq!(segments("f").len(), 0);
+    assert_close(segments("fF")[0].0, Point::new(0.0, L));
+    assert_close(end("F(2.5)"), Point::new(0.0, 2.5));
+}
+
+#[test]
+fn test_turns() {
+    assert_close(end("+F"), Point::new(-L, 0.0));
+    assert_close(end("-F"), Point::new(L, 0.0));
+    assert_close(end("|F"), Point::new(0.0, -L));
+    assert_close(end("+(180)F"), Point::new(0.0, -L));
+}
+
+#[test]
+fn test_branches() {
+    let segments = segments("[+F]F");
+    assert_eq!(segments[0].3, 1);
+    assert_close(segments[1].0, Point::default());
+    assert_close(segments[1].1, Point::new(0.0, L));
+    // An unbalanced `]` is ignored.
+    assert_close(end("]F"), Point::new(0.0, L));
+}
+
+#[test]
+fn test_branch_state() {
+    // Everything a branch changes is restored by `]`.
+    assert_eq!(segments("[#>(&]F"), segments("F"));
+    assert_close(end("[#>(&]+F"), Point::new(-L, 0.0));
+    // And nothing leaks from one interpretation into the next.
+    let (config, state) = (config(2.0), parser::parse_state(">F").unwrap());
+    assert_eq!(
+        turtle::interpret(&state, &config),
+        turtle::interpret(&state, &config)
+    );
+}
+
+#[test]
+fn test_line_width() {
+    assert_eq!(segments("#F")[0].2, 3.0);
+    assert_eq!(segments("#!F")[0].2, 1.0);
+    assert_eq!(segments("#(0.5)F")[0].2, 1.5);
+    assert_eq!(segments("!!F")[0].2, 0.0);
+}
+
+#[test]
+fn test_dot() {
+    assert_eq!(
+        draw(&config(2.0), "#@"),
+        vec![Primitive::Dot {
+            at: Point::default(),
+            radius: 3.0,
+            color: None,
+        }]
+    );
+}
+
+#[test]
+fn test_polygon() {
+    let primitives = draw(&config(2.0), "{f+f+f}.");
+    assert_eq!(primitives.len(), 1);
+    assert!(matches!(&primitives[0], Primitive::Polygon { vertices, .. } if vertices.len() == 4));
+    assert!(draw(&config(2.0), "}.").is_empty());
+}
+
+#[test]
+fn test_line_length() {
+    assert_close(end(">F"), Point::new(0.0, 2.0 * L));
+    assert_close(end("<F"), Point::new(0.0, L / 2.0));
+    assert_close(end("><F"), Point::new(0.0, L));
+    assert_close(end(">(3)F"), Point::new(0.0, 3.0 * L));
+}
+
+#[test]
+fn test_swap_operations() {
+    assert_close(end("&+F"), Point::new(L, 0.0));
+    assert_close(end("&-F"), Point::new(-L, 0.0));
+    assert_close(end("&&+F"), Point::new(-L, 0.0));
+    // The swap is saved and restored by brackets.
+    assert_close(end("[&]+F"), Point::new(-L, 0.0));
+    assert_close(end("&[&]+F"), Point::new(L, 0.0));
+}
+
+#[test]
+fn test_turning_angle() {
+    let diagonal = L * std::f32::consts::FRAC_1_SQRT_2;
+    assert_close(end("(+F"), Point::new(-diagonal, diagonal));
+    assert_close(end(")+F"), Point::new(-diagonal, -diagonal));
+    assert_close(end("((+F"), Point::new(0.0, L));
+    assert_close(end("((90)+F"), Point::new(0.0, L));
+}
+
+#[test]
+fn test_variables_are_ignored() {
+    assert_eq!(segments("XFY"), segments("F"));
+}
+
+#[test]
+fn test_every_symbol() {
+    let symbols = "Ff+-|[]#!@{}.><&()^\\/$X";
+    for config in [config(2.0), config(2.0).three_dimensional()] {
+        for a in symbols.chars() {
+            for b in symbols.chars() {
+                let state = parser::parse_state(&format!("{}{}F", a, b)).unwrap();
+                turtle::interpret(&state, &config);
+            }
+        }
+    }
+}
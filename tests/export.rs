@@ -0,0 +1,241 @@
+use l_system::export::plotter::{self, Format};
+use l_system::export::png;
+use l_system::export::{Plotter, Png, Svg};
+use l_system::graphics::{turtle, Config, Rgba};
+use l_system::state;
+use l_system::system::State;
+
+#[test]
+fn test_svg_paths_by_depth() {
+    let state: State = state!("F[+F][-F]F");
+    let config = Config::new(10, 1.0, 1.1, 90.0, 45.0);
+    let svg = Svg {
+        padding: 0.0,
+        ..Svg::default()
+    }
+    .render(&state, &config);
+
+    assert!(svg.contains(r#"viewBox="-10.5 -20.5 21 21""#));
+    assert!(svg.contains(r#"<g id="depth-0""#));
+    assert!(svg.contains(r#"<g id="depth-1""#));
+    assert!(svg.contains(r#"<path d="M0 0 L0 -10 L0 -20" stroke-width="1"/>"#));
+    assert!(svg.contains(r#"<path d="M0 -10 L-10 -10" stroke-width="1"/>"#));
+    assert!(svg.contains(r#"<path d="M0 -10 L10 -10" stroke-width="1"/>"#));
+    assert_eq!(svg.matches("<path").count(), 3);
+}
+
+#[test]
+fn test_svg_polygons() {
+    let state: State = state!("{f+f+f}");
+    let config = Config::new(10, 1.0, 1.1, 90.0, 45.0);
+    let svg = Svg::default().render(&state, &config);
+
+    assert!(svg.contains(r##"<polygon points="0,0 0,-10 -10,-10 -10,0" fill="#4c9a2a"/>"##));
+    assert!(svg.find("<polygon") < svg.find("</svg>"));
+}
+
+#[test]
+fn test_svg_size() {
+    let state: State = state!("-F-F-F");
+    let config = Config::new(10, 1.0, 1.1, 90.0, 45.0);
+    let svg = Svg {
+        padding: 0.0,
+        size: Some((800.0, 600.0)),
+        ..Svg::default()
+    }
+    .render(&state, &config);
+
+    assert!(svg.contains(r#"viewBox="-0.5 -0.5 11 11" width="800" height="600""#));
+}
+
+// The image data of an encoded PNG, from its stored deflate blocks.
+fn png_data(png: &[u8]) -> Vec<u8> {
+    let idat = png.windows(4).position(|w| w == b"IDAT").unwrap();
+    let length = u32::from_be_bytes(png[idat - 4..idat].try_into().unwrap()) as usize;
+    let mut stream = &png[idat + 4 + 2..idat + 4 + length - 4];
+    let mut data = Vec::new();
+    loop {
+        let last = stream[0] & 1 == 1;
+        let size = u16::from_le_bytes([stream[1], stream[2]]) as usize;
+        data.extend_from_slice(&stream[5..5 + size]);
+        stream = &stream[5 + size..];
+        if last {
+            return data;
+        }
+    }
+}
+
+#[test]
+fn test_png_encode() {
+    let pixels = [255, 0, 0, 255, 0, 255, 0, 128];
+    let png = png::encode(2, 1, &pixels);
+
+    assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+    assert_eq!(&png[12..16], b"IHDR");
+    assert_eq!(&png[16..24], &[0, 0, 0, 2, 0, 0, 0, 1]);
+    assert_eq!(&png[24..26], &[8, 6]);
+    assert!(png.ends_with(b"IEND\xae\x42\x60\x82"));
+    assert_eq!(png_data(&png), [0, 255, 0, 0, 255, 0, 255, 0, 128]);
+}
+
+#[test]
+fn test_png_encode_large() {
+    // More than one stored block.
+    let pixels = vec![7; 200 * 100 * 4];
+    let data = png_data(&png::encode(200, 100, &pixels));
+
+    assert_eq!(data.len(), 100 * (1 + 200 * 4));
+    assert!(data
+        .chunks(801)
+        .all(|row| row[0] == 0 && row[1..] == [7; 800]));
+}
+
+#[test]
+fn test_png_check() {
+    assert!(Png::default().check().is_ok());
+    let huge = Png {
+        width: 20000,
+        height: 20000,
+        ..Png::default()
+    };
+    assert!(huge.check().is_err());
+    let overflowing = Png {
+        width: u32::MAX,
+        height: u32::MAX,
+        supersampling: u32::MAX,
+        ..Png::default()
+    };
+    assert!(overflowing.check().is_err());
+    assert!(overflowing.rasterize(&[]).is_err());
+    let empty = Png {
+        width: 0,
+        ..Png::default()
+    };
+    assert!(empty.check().is_err());
+
+    let path = std::env::temp_dir().join("l_system_huge.png");
+    let error = huge
+        .save(State::new(), &Config::default(), path.to_str().unwrap())
+        .unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+    assert!(!path.exists());
+}
+
+#[test]
+fn test_png_rasterize() {
+    let state: State = state!("F");
+    let config = Config::new(10, 1.0, 1.1, 90.0, 45.0);
+    // The line falls in the middle of a column of pixels.
+    let png = Png {
+        width: 21,
+        height: 20,
+        padding: 2.0,
+        background: Rgba::new(255, 255, 255, 255),
+        stroke: Rgba::new(0, 0, 255, 255),
+        ..Png::default()
+    };
+    let pixels = png.rasterize(&turtle::interpret(&state, &config)).unwrap();
+    let pixel = |x: usize, y: usize| &pixels[(y * 21 + x) * 4..(y * 21 + x) * 4 + 4];
+
+    assert_eq!(pixels.len(), 21 * 20 * 4);
+    assert_eq!(pixel(0, 0), [255, 255, 255, 255]);
+    assert_eq!(pixel(10, 10), [0, 0, 255, 255]);
+    // Anti-aliased at the round cap.
+    let edge = (0..21)
+        .map(|x| pixel(x, 2)[0])
+        .filter(|&r| 0 < r && r < 255);
+    assert!(edge.count() > 0);
+}
+
+#[test]
+fn test_png_polygon_fill() {
+    let state: State = state!("{f+f+f+f}");
+    let config = Config::new(10, 1.0, 1.1, 90.0, 45.0);
+    let png = Png {
+        width: 10,
+        height: 10,
+        padding: 0.0,
+        ..Png::default()
+    };
+    let pixels = png.rasterize(&turtle::interpret(&state, &config)).unwrap();
+
+    assert!(pixels.chunks(4).all(|p| p == [0x4c, 0x9a, 0x2a, 255]));
+}
+
+#[test]
+fn test_plotter_merges_chained_lines() {
+    let state: State = state!("F+F+F");
+    let config = Config::new(10, 1.0, 1.1, 90.0, 45.0);
+    let plotter = Plotter {
+        page: (40.0, 40.0),
+        margin: 10.0,
+        ..Plotter::default()
+    };
+    let strokes = plotter.strokes(&turtle::interpret(&state, &config));
+
+    // A square missing a side, scaled to the 20mm between the margins.
+    assert_eq!(strokes.len(), 1);
+    let mut points: Vec<_> = strokes[0]
+        .iter()
+        .map(|&(x, y)| (x.round(), y.round()))
+        .collect();
+    points.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    assert_eq!(
+        points,
+        [(10.0, 10.0), (10.0, 30.0), (30.0, 10.0), (30.0, 30.0)]
+    );
+}
+
+#[test]
+fn test_plotter_moves_are_travel() {
+    let state: State = state!("FfF");
+    let config = Config::new(10, 1.0, 1.1, 90.0, 45.0);
+    let gcode = Plotter::default().render(&state, &config);
+
+    assert_eq!(gcode.matches("G0 Z0").count(), 2);
+    assert_eq!(gcode.matches("G0 Z5").count(), 3);
+    assert!(gcode.contains("F1500"));
+    assert!(gcode.trim_end().ends_with("G1 X0.000 Y0.000 F3000"));
+}
+
+#[test]
+fn test_plotter_reduces_travel() {
+    // Every branch ends away from where the next one starts.
+    let state: State = state!("F[+F[+F][-F]F][-F[+F][-F]F]F[+F][-F]F");
+    let config = Config::new(10, 1.0, 1.1, 30.0, 45.0);
+    let primitives = turtle::interpret(&state, &config);
+    let unoptimized = Plotter {
+        optimize: false,
+        ..Plotter::default()
+    }
+    .strokes(&primitives);
+    let optimized = Plotter::default().strokes(&primitives);
+
+    let drawn = |strokes: &[Vec<(f32, f32)>]| {
+        strokes
+            .iter()
+            .flat_map(|s| s.windows(2))
+            .map(|w| (w[0].0 - w[1].0).hypot(w[0].1 - w[1].1))
+            .sum::<f32>()
+    };
+    assert!((drawn(&optimized) - drawn(&unoptimized)).abs() < 1e-2);
+    assert!(optimized.len() <= unoptimized.len());
+    assert!(plotter::travel(&optimized) < plotter::travel(&unoptimized));
+}
+
+#[test]
+fn test_plotter_hpgl() {
+    let state: State = state!("F@");
+    let config = Config::new(10, 1.0, 1.1, 90.0, 45.0);
+    let hpgl = Plotter {
+        format: Format::Hpgl,
+        page: (40.0, 40.0),
+        margin: 10.0,
+        ..Plotter::default()
+    }
+    .render(&state, &config);
+
+    assert!(hpgl.starts_with("IN;SP1;VS2.5;"));
+    assert!(hpgl.contains("PU800,400;PD800,1200;"));
+    assert!(hpgl.trim_end().ends_with("PU0,0;SP0;"));
+}
@@ -0,0 +1,39 @@
+use l_system::system::Expression;
+
+fn eval(text: &str, values: &[f32]) -> f32 {
+    Expression::parse(text, &["x", "y"]).unwrap().eval(values)
+}
+
+#[test]
+fn test_arithmetic() {
+    assert_eq!(eval("1 + 2 * 3", &[]), 7.0);
+    assert_eq!(eval("(1 + 2) * 3", &[]), 9.0);
+    assert_eq!(eval("-x ^ 2", &[3.0]), -9.0);
+    assert_eq!(eval("2 ^ 3 ^ 2", &[]), 512.0);
+    assert_eq!(eval("x / y % 4", &[14.0, 2.0]), 3.0);
+    assert_eq!(eval("max(x, sqrt(y))", &[1.0, 16.0]), 4.0);
+}
+
+#[test]
+fn test_conditions() {
+    assert_eq!(eval("x > 2 && y <= 1", &[3.0, 1.0]), 1.0);
+    assert_eq!(eval("x > 2 && y <= 1", &[3.0, 2.0]), 0.0);
+    assert_eq!(eval("x == 1 || !(y != 2)", &[0.0, 2.0]), 1.0);
+}
+
+#[test]
+fn test_errors() {
+    assert!(Expression::parse("z + 1", &["x"]).is_err());
+    assert!(Expression::parse("(x + 1", &["x"]).is_err());
+    assert!(Expression::parse("x +", &["x"]).is_err());
+    assert!(Expression::parse("min(x)", &["x"]).is_err());
+}
+
+#[test]
+fn test_display() {
+    let parameters = ["x", "y"];
+    let expression = Expression::parse("-x * (y + 0.5) >= min(x, 2)", &parameters).unwrap();
+    let text = expression.display(&parameters).to_string();
+
+    assert_eq!(Expression::parse(&text, &parameters).unwrap(), expression);
+}
@@ -0,0 +1,158 @@
+use l_system::graphics::{turtle, Coloring, Config, Rgba};
+use l_system::parser::{self, ParseErrorKind};
+use l_system::system::{Module, Symbol, System};
+use l_system::{state, symbol};
+use std::collections::HashMap;
+
+mod common;
+
+use common::{colors, config, draw};
+
+const BLACK: Rgba = Rgba::new(0, 0, 0, 255);
+const WHITE: Rgba = Rgba::new(255, 255, 255, 255);
+const GREY: Rgba = Rgba::new(128, 128, 128, 255);
+
+fn gradient(coloring: Coloring) -> Config {
+    Config {
+        coloring,
+        gradient: vec![BLACK, WHITE],
+        ..config(1.0)
+    }
+}
+
+// X -> F[+X]FX, from X.
+fn plant() -> System {
+    let mut rules = HashMap::new();
+    rules.insert(symbol!('X'), state!("F[+X]FX"));
+    System::new(rules, state!("X"))
+}
+
+fn births(modules: impl IntoIterator<Item = Module>) -> Vec<u32> {
+    modules.into_iter().map(|m| m.birth).collect()
+}
+
+#[test]
+fn test_parse_coloring() {
+    let source =
+        "[config]\ncoloring = Depth\ngradient = #000000 #ffffff80\n\n[rules]\n\n[start]\naxiom = F";
+    let (config, system) = parser::parse_str(source).unwrap();
+    assert_eq!(config.coloring, Coloring::Depth);
+    assert_eq!(config.gradient, [BLACK, Rgba::new(255, 255, 255, 128)]);
+
+    let text = parser::write(&config, &system);
+    assert!(text.contains("coloring = depth\ngradient = #000000 #ffffff80\n"));
+    let (reparsed, _) = parser::parse_str(&text).unwrap();
+    assert_eq!(reparsed.coloring, config.coloring);
+    assert_eq!(reparsed.gradient, config.gradient);
+
+    let (config, system) = parser::parse_str("[config]\n\n[rules]\n\n[start]\naxiom = F").unwrap();
+    assert_eq!(config.coloring, Coloring::Palette);
+    assert_eq!(config.gradient, Config::DEFAULT_GRADIENT);
+    assert!(!parser::write(&config, &system).contains("gradient"));
+}
+
+#[test]
+fn test_coloring_errors() {
+    let kind = |source: &str| parser::parse_str(source).unwrap_err().kind;
+
+    assert!(matches!(
+        kind("[config]\ncoloring = age\n\n[rules]\n\n[start]\naxiom = F"),
+        ParseErrorKind::BadSetting(s) if s.starts_with("Coloring: `age` should be")
+    ));
+    assert!(matches!(
+        kind("[config]\ngradient = #000000 white\n\n[rules]\n\n[start]\naxiom = F"),
+        ParseErrorKind::BadSetting(_)
+    ));
+}
+
+#[test]
+fn test_gradient_at() {
+    let mut config = Config {
+        gradient: vec![BLACK, WHITE],
+        ..Config::default()
+    };
+    assert_eq!(config.gradient_at(0.0), Some(BLACK));
+    assert_eq!(config.gradient_at(0.5), Some(GREY));
+    assert_eq!(config.gradient_at(1.0), Some(WHITE));
+    assert_eq!(config.gradient_at(2.0), Some(WHITE));
+
+    config.gradient = vec![BLACK, WHITE, BLACK];
+    assert_eq!(config.gradient_at(0.5), Some(WHITE));
+    assert_eq!(config.gradient_at(0.75), Some(GREY));
+
+    config.gradient = vec![GREY];
+    assert_eq!(config.gradient_at(0.3), Some(GREY));
+    config.gradient.clear();
+    assert_eq!(config.gradient_at(0.3), None);
+}
+
+#[test]
+fn test_depth_coloring() {
+    let primitives = draw(&gradient(Coloring::Depth), "F[+F[+F]{f+f+f}]@");
+
+    assert_eq!(
+        colors(&primitives),
+        [
+            Some(BLACK),
+            Some(GREY),
+            Some(WHITE),
+            Some(GREY),
+            Some(BLACK)
+        ]
+    );
+}
+
+#[test]
+fn test_distance_coloring() {
+    // The palette gives way to the gradient.
+    let config = Config {
+        palette: vec![Rgba::new(255, 0, 0, 255)],
+        ..gradient(Coloring::Distance)
+    };
+    let primitives = draw(&config, "Ff[+F]F");
+
+    assert_eq!(colors(&primitives), [Some(BLACK), Some(WHITE), Some(WHITE)]);
+}
+
+#[test]
+fn test_births() {
+    let mut rules = HashMap::new();
+    rules.insert(symbol!('A'), state!("AB"));
+    let system = System::new(rules, state!("A"));
+    let state = system.with_births(true).into_iter().nth(3).unwrap();
+    // Every module a production creates is new, `A` included.
+    assert_eq!(births(state), [3, 3, 2, 1]);
+
+    let system = plant();
+    let state = system.clone().with_births(true).into_iter().nth(2).unwrap();
+    // F[+X]FX, then F[+F[+X]FX]FF[+X]FX: the first generation's lines stay.
+    let lines = state.iter().filter(|m| m.symbol == Symbol::Draw).cloned();
+    assert_eq!(births(lines), [1, 2, 2, 1, 2, 2]);
+
+    // The stream and parallel rewriting tag the same births.
+    let state = system
+        .clone()
+        .with_births(true)
+        .into_iter()
+        .nth(12)
+        .unwrap();
+    assert!(state.len() > 2 * 4096);
+    assert_eq!(births(system.clone().stream(12)), births(state.clone()));
+    let parallel = system.with_births(true).with_threads(4);
+    assert_eq!(births(parallel.into_iter().nth(12).unwrap()), births(state));
+    // Births don't take part in equality.
+    assert_eq!(Module::from('F'), {
+        let mut module = Module::from('F');
+        module.birth = 3;
+        module
+    });
+}
+
+#[test]
+fn test_generation_coloring() {
+    let config = gradient(Coloring::Generation);
+    let primitives = turtle::interpret(plant().stream(2), &config);
+    let (old, new) = (Some(BLACK), Some(WHITE));
+
+    assert_eq!(colors(&primitives), [old, new, new, old, new, new]);
+}
@@ -0,0 +1,50 @@
+mod common;
+
+use common::system;
+use l_system::system::{History, State, System};
+
+fn expand(system: &System, generation: usize) -> State {
+    system.clone().into_iter().nth(generation).unwrap()
+}
+
+#[test]
+fn test_history_matches_iteration() {
+    for path in ["systems/stochastic.lsys", "systems/context.lsys"] {
+        let system = system(path);
+        // Nothing but the axiom and the last generation fits.
+        let mut history = History::new(system.clone(), 0);
+        for generation in [4, 2, 5, 0, 3, 3, 1] {
+            assert_eq!(history.get(generation), &expand(&system, generation));
+        }
+    }
+}
+
+#[test]
+fn test_history_budget() {
+    let system = system("systems/branch.lsys");
+    let mut unbounded = History::new(system.clone(), usize::MAX);
+    unbounded.get(3);
+    assert!((0..=3).all(|g| unbounded.is_cached(g)));
+
+    let budget = unbounded.used();
+    let mut history = History::new(system, budget);
+    history.get(3);
+    assert_eq!(history.used(), budget);
+
+    // Expanding past the budget drops the least recently used first.
+    history.get(1);
+    history.get(4);
+    assert!(history.used() <= budget || (1..4).all(|g| !history.is_cached(g)));
+    assert!(history.is_cached(0) && history.is_cached(4));
+    assert!(history.is_cached(1) || (2..4).all(|g| !history.is_cached(g)));
+}
+
+#[test]
+fn test_resume() {
+    let system = system("systems/stochastic.lsys");
+    let state = expand(&system, 3);
+    let mut iterator = system.clone().resume(state, 3);
+    assert_eq!(iterator.generation(), 3);
+    iterator.next();
+    assert_eq!(iterator.state(), &expand(&system, 4));
+}
@@ -0,0 +1,170 @@
+use l_system::export::mesh::{Format, Geometry};
+use l_system::export::Mesh;
+use l_system::graphics::{turtle, Config, Point};
+use l_system::state;
+use l_system::system::State;
+use std::collections::HashMap;
+
+fn geometry(state: &State, config: &Config) -> Geometry {
+    Mesh::default().geometry(&turtle::interpret(state, config))
+}
+
+// Every edge is shared by exactly two triangles, going opposite ways.
+fn is_closed(geometry: &Geometry) -> bool {
+    let mut edges = HashMap::<(u32, u32), i32>::new();
+    for &[a, b, c] in &geometry.triangles {
+        for (from, to) in [(a, b), (b, c), (c, a)] {
+            *edges.entry((from, to)).or_default() += 1;
+        }
+    }
+    edges
+        .iter()
+        .all(|(&(from, to), &count)| count == 1 && edges.get(&(to, from)) == Some(&1))
+}
+
+// Positive when the triangles face outwards.
+fn volume(geometry: &Geometry) -> f32 {
+    geometry
+        .triangles
+        .iter()
+        .map(|&[a, b, c]| {
+            let [a, b, c] = [a, b, c].map(|i| geometry.vertices[i as usize]);
+            a.dot(b.cross(c)) / 6.0
+        })
+        .sum()
+}
+
+#[test]
+fn test_mesh_tube() {
+    let state: State = state!("F");
+    let config = Config::new(10, 1.0, 1.1, 90.0, 45.0);
+    let geometry = geometry(&state, &config);
+
+    // Two rings of 8 and the centers of both caps.
+    assert_eq!(geometry.vertices.len(), 18);
+    assert_eq!(geometry.triangles.len(), 32);
+    assert!(is_closed(&geometry));
+    // An octagonal prism of radius 0.5 and height 10.
+    let expected = 8.0 * 0.5 * 0.25 * (std::f32::consts::PI / 4.0).sin() * 10.0;
+    assert!((volume(&geometry) - expected).abs() < 1e-3);
+}
+
+#[test]
+fn test_mesh_joins_chained_lines() {
+    let state: State = state!("F+F#F&(30)F");
+    let config = Config::new(10, 1.0, 1.1, 30.0, 45.0);
+    let geometry = geometry(&state, &config);
+
+    // One tube: a ring per joint and two caps.
+    assert_eq!(geometry.vertices.len(), 5 * 8 + 2);
+    assert!(is_closed(&geometry));
+    assert!(volume(&geometry) > 0.0);
+}
+
+#[test]
+fn test_mesh_mitered_bend() {
+    let state: State = state!("F+F");
+    let config = Config::new(10, 1.0, 1.1, 90.0, 45.0);
+    let geometry = geometry(&state, &config);
+
+    // Both octagonal prisms whole, up to the cut between them.
+    let prism = 8.0 * 0.5 * 0.25 * (std::f32::consts::PI / 4.0).sin() * 10.0;
+    assert!((volume(&geometry) - 2.0 * prism).abs() < 1e-3);
+    // The joint is as thick as the tubes: every vertex but the centers of
+    // the caps lies on the edge of a prism.
+    let distance = |p: Point, from: Point, along: Point| {
+        let offset = p - from;
+        (offset - along * offset.dot(along)).length()
+    };
+    let axes = [
+        (Point::new(0.0, 0.0), Point::new(0.0, 1.0)),
+        (Point::new(0.0, 10.0), Point::new(-1.0, 0.0)),
+    ];
+    let corners = geometry
+        .vertices
+        .iter()
+        .map(|&p| axes.map(|(from, along)| distance(p, from, along)))
+        .filter(|d| d.iter().all(|&d| d > 1e-3));
+    for d in corners {
+        assert!((d[0].min(d[1]) - 0.5).abs() < 1e-3);
+    }
+}
+
+#[test]
+fn test_mesh_branches_are_closed() {
+    let state: State = state!("F[+F@][-F|F]F{.-f+f+f}");
+    let config = Config::new(10, 1.0, 1.1, 60.0, 45.0);
+    let geometry = geometry(&state, &config);
+
+    assert!(is_closed(&geometry));
+    assert!(volume(&geometry) > 0.0);
+}
+
+#[test]
+fn test_mesh_polygon_face() {
+    let state: State = state!("{f+f+f}");
+    let config = Config::new(10, 1.0, 1.1, 90.0, 45.0);
+    let mesh = Mesh {
+        leaf_thickness: 0.0,
+        ..Mesh::default()
+    };
+    let geometry = mesh.geometry(&turtle::interpret(&state, &config));
+
+    assert_eq!(geometry.vertices.len(), 4);
+    assert_eq!(geometry.triangles.len(), 2);
+}
+
+#[test]
+fn test_mesh_formats() {
+    let state: State = state!("F");
+    let config = Config::new(10, 1.0, 1.1, 90.0, 45.0);
+    let render = |format| {
+        Mesh {
+            format,
+            ..Mesh::default()
+        }
+        .render(&state, &config)
+    };
+
+    let obj = String::from_utf8(render(Format::Obj)).unwrap();
+    assert_eq!(obj.lines().filter(|l| l.starts_with("v ")).count(), 18);
+    assert_eq!(obj.lines().filter(|l| l.starts_with("f ")).count(), 32);
+    // Indices start at 1.
+    assert!(obj
+        .lines()
+        .filter_map(|l| l.strip_prefix("f "))
+        .flat_map(|f| f.split(' '))
+        .all(|i| (1..=18).contains(&i.parse().unwrap())));
+
+    let ply = String::from_utf8(render(Format::Ply)).unwrap();
+    assert!(ply.starts_with("ply\nformat ascii 1.0\nelement vertex 18\n"));
+    assert!(ply.contains("element face 32\n"));
+
+    let stl = String::from_utf8(render(Format::StlAscii)).unwrap();
+    assert!(stl.starts_with("solid"));
+    assert_eq!(stl.matches("facet normal").count(), 32);
+
+    let stl = render(Format::StlBinary);
+    assert_eq!(stl.len(), 84 + 32 * 50);
+    assert_eq!(&stl[80..84], &32u32.to_le_bytes());
+}
+
+#[test]
+fn test_mesh_stl_stands_on_z() {
+    let state: State = state!("F");
+    let config = Config::new(10, 1.0, 1.1, 90.0, 45.0);
+    let stl = Mesh {
+        format: Format::StlAscii,
+        ..Mesh::default()
+    }
+    .render(&state, &config);
+    let heights: Vec<f32> = String::from_utf8(stl)
+        .unwrap()
+        .lines()
+        .filter_map(|l| l.trim().strip_prefix("vertex "))
+        .map(|v| v.split(' ').nth(2).unwrap().parse().unwrap())
+        .collect();
+
+    assert!(heights.iter().all(|&z| (-1e-3..=10.001).contains(&z)));
+    assert!(heights.iter().any(|&z| z > 9.9));
+}
@@ -0,0 +1,133 @@
+use l_system::export::plotter::Format;
+use l_system::export::{Plotter, Png, Svg};
+use l_system::graphics::{Config, Rgba};
+use l_system::parser::{self, ParseErrorKind};
+
+mod common;
+
+use common::{colors, config, draw};
+
+const BROWN: Rgba = Rgba::new(139, 90, 43, 255);
+const GREEN: Rgba = Rgba::new(60, 141, 47, 255);
+const PINK: Rgba = Rgba::new(255, 105, 180, 255);
+
+fn palette() -> Config {
+    Config {
+        palette: vec![BROWN, GREEN, PINK],
+        ..config(1.0)
+    }
+}
+
+#[test]
+fn test_parse_palette() {
+    let palette = "[palette]\n0 = #8b5a2b\n1 = #3c8d2f\n2 = #ff69b480";
+    let source = format!(
+        "[config]\n\n{}\n\n[rules]\n\n[start]\naxiom = F'F'(2)F\n",
+        palette
+    );
+    let (config, system) = parser::parse_str(&source).unwrap();
+
+    assert_eq!(
+        config.palette,
+        [BROWN, GREEN, Rgba::new(255, 105, 180, 128)]
+    );
+    let text = parser::write(&config, &system);
+    assert!(text.contains(&format!("\n{}\n\n[rules]", palette)));
+    assert!(text.contains("axiom = F'F'(2)F\n"));
+    let (reparsed, _) = parser::parse_str(&text).unwrap();
+    assert_eq!(reparsed.palette, config.palette);
+}
+
+#[test]
+fn test_palette_errors() {
+    let kind = |source: &str| parser::parse_str(source).unwrap_err().kind;
+
+    assert!(matches!(
+        kind("[config]\n\n[palette]\n0 = #000000\n2 = #ffffff\n\n[rules]\n\n[start]\naxiom = F"),
+        ParseErrorKind::BadSetting(s) if s == "Palette: expected colour `1`, found `2`."
+    ));
+    assert!(matches!(
+        kind("[config]\n\n[palette]\n0 = brown\n\n[rules]\n\n[start]\naxiom = F"),
+        ParseErrorKind::BadSetting(_)
+    ));
+    assert!(matches!(
+        kind("[config]\n\n[palette]\n0 #000000\n\n[rules]\n\n[start]\naxiom = F"),
+        ParseErrorKind::BadSetting(_)
+    ));
+}
+
+#[test]
+fn test_color_index() {
+    let primitives = draw(&palette(), "F'F[''F]F'(0)F'''F");
+
+    assert_eq!(
+        colors(&primitives),
+        [
+            Some(BROWN),
+            Some(GREEN),
+            Some(BROWN),
+            Some(GREEN),
+            Some(BROWN),
+            // Wrapping around the palette.
+            Some(BROWN)
+        ]
+    );
+}
+
+#[test]
+fn test_color_of_dots_and_polygons() {
+    let primitives = draw(&palette(), "'@'(2){f+f+f}");
+    assert_eq!(colors(&primitives), [Some(GREEN), Some(PINK)]);
+
+    // Without a palette, renderers pick, and polygons keep the fill colour.
+    let primitives = draw(&config(1.0), "'@'(2){f+f+f}");
+    assert_eq!(colors(&primitives), [None, Some(Config::DEFAULT_FILL)]);
+}
+
+#[test]
+fn test_svg_colors() {
+    let svg = Svg::default().render_primitives(&draw(&palette(), "F'F[+'F@]"));
+
+    assert!(svg.contains(r##"<path d="M0 0 L0 -10" stroke-width="1" stroke="#8b5a2b"/>"##));
+    assert!(svg.contains(r##"<path d="M0 -10 L0 -20" stroke-width="1" stroke="#3c8d2f"/>"##));
+    assert!(svg.contains(r##"stroke-width="1" stroke="#ff69b4"/>"##));
+    assert!(svg.contains(r##"fill="#ff69b4"/>"##));
+
+    let svg = Svg::default().render_primitives(&draw(&config(1.0), "F'F"));
+    assert!(svg.contains("<path d=\"M0 0 L0 -10 L0 -20\" stroke-width=\"1\"/>"));
+}
+
+#[test]
+fn test_png_colors() {
+    let png = Png {
+        width: 21,
+        height: 20,
+        padding: 2.0,
+        ..Png::default()
+    };
+    let pixels = png.rasterize(&draw(&palette(), "'F")).unwrap();
+    let center = (10 * 21 + 10) * 4;
+
+    assert_eq!(pixels[center..center + 4], [GREEN.r, GREEN.g, GREEN.b, 255]);
+}
+
+#[test]
+fn test_plotter_pens() {
+    let primitives = draw(&palette(), "F'F+F'(0)F");
+
+    let gcode = Plotter::default().render_primitives(&primitives);
+    assert_eq!(gcode.matches("M0 ; pen").count(), 2);
+    let brown = gcode.find("M0 ; pen #8b5a2b").unwrap();
+    let green = gcode.find("M0 ; pen #3c8d2f").unwrap();
+    assert!(brown < green);
+    // Both brown lines are plotted before changing pens.
+    assert_eq!(gcode[brown..green].matches("G0 Z0").count(), 2);
+
+    let hpgl = Plotter {
+        format: Format::Hpgl,
+        ..Plotter::default()
+    }
+    .render_primitives(&primitives);
+    assert!(hpgl.contains("SP2;"));
+    assert!(!hpgl.contains("SP3;"));
+}
@@ -0,0 +1,56 @@
+use l_system::parser;
+use l_system::system::{Compact, System};
+
+const CONFIG: &str = "[config]
+line_length = 1
+line_width_increment = 1
+line_length_scale_factor = 1
+turning_angle = 90
+turning_angle_increment = 1";
+
+fn system(rules: &str, axiom: &str) -> System {
+    let source = format!(
+        "{}\n\n[rules]\n{}\n\n[start]\naxiom = {}",
+        CONFIG, rules, axiom
+    );
+    parser::parse_str(&source).unwrap().1
+}
+
+fn assert_same_generations(system: System, generations: usize) {
+    let sequential = system.clone().into_iter().take(generations);
+    let parallel = system.with_threads(4).into_iter().take(generations);
+    for (generation, (a, b)) in sequential.zip(parallel).enumerate() {
+        assert_eq!(a, b, "generation {}", generation);
+    }
+}
+
+#[test]
+fn test_parallel_compact() {
+    let (_, stochastic) = parser::parse("systems/stochastic.lsys").unwrap();
+    assert!(Compact::new(stochastic.rules()).is_some());
+    assert_same_generations(stochastic, 8);
+}
+
+#[test]
+fn test_parallel_modules() {
+    // Parameters and context rule out ids, the states grow past a chunk.
+    let parametric = system("A(x) -> A(x+1)[+B(x)]A(x/2)", "A(1)");
+    assert_same_generations(parametric, 13);
+    let context = system("A < B -> BA\nB -> AB", "AB");
+    assert_same_generations(context, 16);
+}
+
+#[test]
+fn test_parallel_compact_rewrite() {
+    let (_, system) = parser::parse("systems/branch.lsys").unwrap();
+    let mut compact = Compact::new(system.rules()).unwrap();
+    let state: Vec<u16> = compact
+        .encode(&system.clone().into_iter().nth(6).unwrap())
+        .unwrap();
+
+    let mut sequential = Vec::new();
+    compact.rewrite(&state, 0, 6, &mut sequential);
+    let mut parallel = vec![7; 3];
+    compact.rewrite_parallel(&state, 0, 6, 8, &mut parallel);
+    assert_eq!(sequential, parallel);
+}
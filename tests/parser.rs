@@ -0,0 +1,207 @@
+use l_system::parser::{self, LSystem, ParseError, ParseErrorKind};
+use l_system::system::dump;
+
+#[test]
+fn test_parse_branch() {
+    let (config, system) = parser::parse("systems/branch.lsys").unwrap();
+
+    assert_eq!(config.line_length, 3);
+    assert_eq!(system.seed(), 0);
+    assert_eq!(dump(&system.into_iter().next().unwrap()), "X");
+}
+
+#[test]
+fn test_parse_stochastic() {
+    let (_, system) = parser::parse("systems/stochastic.lsys").unwrap();
+    assert_eq!(system.seed(), 7);
+
+    let first: Vec<_> = system
+        .clone()
+        .into_iter()
+        .take(5)
+        .map(|s| dump(&s))
+        .collect();
+    let second: Vec<_> = system.into_iter().take(5).map(|s| dump(&s)).collect();
+
+    assert_eq!(first, second);
+    assert_eq!(first[1].matches('F').count() % 2, 1);
+}
+
+#[test]
+fn test_parse_context() {
+    let (_, system) = parser::parse("systems/context.lsys").unwrap();
+    assert_eq!(dump(system.ignore()), "+-F");
+
+    let actual: Vec<_> = system.into_iter().take(3).map(|s| dump(&s)).collect();
+
+    assert_eq!(actual, vec!["F1F1F1", "F1F0F1", "F1F1F1F1"]);
+
+    // A right context ending in parameters is not a weight.
+    let source = "[config]\n\n[rules]\nA > -(x) -> B(x)\n\n[start]\naxiom = A-(2)\n";
+    let (_, system) = parser::parse_str(source).unwrap();
+    assert_eq!(dump(&system.into_iter().nth(1).unwrap()), "B(2)-(2)");
+}
+
+#[test]
+fn test_parse_parametric() {
+    let (_, system) = parser::parse("systems/parametric.lsys").unwrap();
+
+    let actual: Vec<_> = system.into_iter().take(3).map(|s| dump(&s)).collect();
+
+    assert_eq!(
+        actual,
+        vec![
+            "A(64,4)",
+            "#(4)F(64)[+(25)A(32,2)][-A(32,2)]",
+            "#(4)F(64)[+(25)#(2)F(32)[+(25)A(16,1)][-A(16,1)]][-#(2)F(32)[+(25)A(16,1)][-A(16,1)]]",
+        ]
+    );
+}
+
+#[test]
+fn test_parse_modules() {
+    let state = parser::parse_state("F(1.5)+(30) (X").unwrap();
+    assert_eq!(dump(&state), "F(1.5)+(30)(X");
+    assert_eq!(state.len(), 4);
+
+    let patterns = parser::parse_patterns("A(l,w)").unwrap();
+    assert_eq!(patterns[0].params, vec!["l", "w"]);
+    assert!(parser::parse_patterns("A(1)").is_err());
+    assert!(parser::parse_templates("F(q)", &["l"]).is_err());
+}
+
+#[test]
+fn test_parse_paren_symbols() {
+    // Without a well-formed argument list, `(` and `)` are symbols.
+    let state = parser::parse_state("F(+F)X()").unwrap();
+    assert_eq!(dump(&state), "F(+F)X()");
+    assert_eq!(state.len(), 8);
+    assert!(state.iter().all(|m| m.params.is_empty()));
+
+    // Arguments that read as an expression are parameters, and errors
+    // suggest a space when they were meant as symbols.
+    let error = parser::parse_state("F(-F)").unwrap_err();
+    assert!(error.contains("separate it from `F` with whitespace"));
+    assert_eq!(parser::parse_state("F (-F)").unwrap().len(), 5);
+}
+
+#[test]
+fn test_parse_sources() {
+    let source = std::fs::read_to_string("systems/branch.lsys").unwrap();
+    let (config, system) = parser::parse_str(&source).unwrap();
+    assert_eq!(config.line_length, 3);
+    assert_eq!(
+        dump(&system.into_iter().nth(1).unwrap()),
+        "F-[[X]+X]+F[+FX]-X"
+    );
+
+    let (_, system) = parser::parse_reader(source.as_bytes()).unwrap();
+    assert_eq!(dump(&system.into_iter().next().unwrap()), "X");
+
+    let l_system: LSystem = "[config]\n\n[rules]\nF -> F+F\n\n[start]\naxiom = F\nseed = 3\n"
+        .parse()
+        .unwrap();
+    assert_eq!(l_system.system.seed(), 3);
+    assert_eq!(dump(&l_system.system.into_iter().nth(1).unwrap()), "F+F");
+}
+
+fn parse_source(source: &str) -> Result<(), ParseError> {
+    parser::parse_str(source).map(|_| ())
+}
+
+#[test]
+fn test_error_locations() {
+    let source = "[config]\nline_length = 3\nturning_angle = abc\n\n[rules]\nF -> FF\n\n[start]\naxiom = F\n";
+    let error = parse_source(source).unwrap_err();
+
+    assert!(matches!(
+        error.kind,
+        ParseErrorKind::NonNumeric {
+            setting: "turning_angle",
+            ..
+        }
+    ));
+    assert_eq!(error.position(), Some((3, 17)));
+    assert!(error
+        .render()
+        .ends_with("3 | turning_angle = abc\n  |                 ^^^"));
+}
+
+#[test]
+fn test_error_kinds() {
+    let kind = |source| parse_source(source).unwrap_err().kind;
+
+    assert!(matches!(
+        kind("[settings]\n"),
+        ParseErrorKind::UnknownSection(s) if s == "settings"
+    ));
+    assert!(matches!(
+        kind("[config]\nline_lenght = 3\n"),
+        ParseErrorKind::UnknownSetting(s) if s == "line_lenght"
+    ));
+    assert!(matches!(
+        kind("[config]\ndimensions = 4\n"),
+        ParseErrorKind::NonNumeric {
+            setting: "dimensions",
+            ..
+        }
+    ));
+    assert!(matches!(
+        kind("[config]\nline_length 3\n"),
+        ParseErrorKind::BadSetting(_)
+    ));
+    assert!(matches!(
+        kind("[rules]\nF -> FF\nF -> F+F\n"),
+        ParseErrorKind::DuplicateRule(s) if s == "F"
+    ));
+    assert!(matches!(
+        kind("[rules]\nF -(x)-> FF\n"),
+        ParseErrorKind::BadRule(s) if s.starts_with("Rule weight: `x`")
+    ));
+    assert!(matches!(
+        kind("[config]\n\n[rules]\nF -> FF\n\n[start]\nseed = 1\n"),
+        ParseErrorKind::MissingAxiom
+    ));
+    assert!(matches!(
+        kind("[rules]\nF -> FF\n"),
+        ParseErrorKind::MissingSections(s) if s == vec!["config", "start"]
+    ));
+    assert!(matches!(
+        parser::parse("systems/missing.lsys").unwrap_err().kind,
+        ParseErrorKind::Io(_)
+    ));
+}
+
+#[test]
+fn test_write_round_trip() {
+    for name in [
+        "branch",
+        "branch_gradient",
+        "stochastic",
+        "context",
+        "parametric",
+    ] {
+        let path = format!("systems/{}.lsys", name);
+        let (config, system) = parser::parse(&path).unwrap();
+        let text = parser::write(&config, &system);
+        let (reparsed_config, reparsed) = parser::parse_str(&text).unwrap();
+
+        assert_eq!(parser::write(&reparsed_config, &reparsed), text, "{}", name);
+        assert_eq!(reparsed_config.turning_angle, config.turning_angle);
+        let expected: Vec<_> = system.into_iter().take(5).map(|s| dump(&s)).collect();
+        let actual: Vec<_> = reparsed.into_iter().take(5).map(|s| dump(&s)).collect();
+        assert_eq!(actual, expected, "{}", name);
+    }
+}
+
+#[test]
+fn test_write_canonical() {
+    let source = "[config]\nturning_angle = 22.5\n\n[rules]\nX -> F-[[X]+X]+F[+FX]-X\nF -(1)-> FF\nF -(1)-> F (F\nA(x) : x > -(x + 1) -(1)-> A(-x*(x-1)^2)\n\n[start]\naxiom = X\n";
+    let l_system: LSystem = source.parse().unwrap();
+    let text = l_system.to_string();
+
+    assert!(text.contains("turning_angle = 22.5\n"));
+    assert!(text.contains("A(x) : x > -(x + 1) -> A(-x * (x - 1) ^ 2)\n"));
+    assert!(text.contains("F -(1)-> FF\nF -(1)-> F (F\nX -> F-[[X]+X]+F[+FX]-X\n"));
+    assert!(text.ends_with("[start]\naxiom = X\nseed = 0\n"));
+}
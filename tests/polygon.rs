@@ -0,0 +1,89 @@
+use l_system::graphics::polygon::triangulate;
+use l_system::graphics::turtle::{self, Point, Primitive};
+use l_system::graphics::{Config, Rgba};
+use l_system::parser;
+
+fn area(vertices: &[Point], triangles: &[[usize; 3]]) -> f32 {
+    triangles
+        .iter()
+        .map(|&[a, b, c]| {
+            let (a, b, c) = (vertices[a], vertices[b], vertices[c]);
+            (b - a).cross(c - a).length() / 2.0
+        })
+        .sum()
+}
+
+fn polygons(source: &str, config: &Config) -> Vec<Vec<Point>> {
+    let state = parser::parse_state(source).unwrap();
+    turtle::interpret(&state, config)
+        .into_iter()
+        .filter_map(|p| match p {
+            Primitive::Polygon { vertices, .. } => Some(vertices),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn test_triangulate_concave() {
+    // An L shape, clockwise.
+    let l = [
+        (0.0, 0.0),
+        (0.0, 2.0),
+        (1.0, 2.0),
+        (1.0, 1.0),
+        (2.0, 1.0),
+        (2.0, 0.0),
+    ]
+    .map(|(x, y)| Point::new(x, y));
+    let triangles = triangulate(&l);
+
+    assert_eq!(triangles.len(), 4);
+    assert!((area(&l, &triangles) - 3.0).abs() < 1e-5);
+}
+
+#[test]
+fn test_triangulate_3d() {
+    // A unit square standing in the yz plane.
+    let square =
+        [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)].map(|(y, z)| Point::xyz(2.0, y, z));
+    let triangles = triangulate(&square);
+
+    assert_eq!(triangles.len(), 2);
+    assert!((area(&square, &triangles) - 1.0).abs() < 1e-5);
+}
+
+#[test]
+fn test_polygon_vertices() {
+    let config = Config::new(10, 1.0, 1.1, 90.0, 45.0);
+
+    // `.` records a vertex, moving records one too, without duplicates.
+    let square = polygons("{.f.+f.+f.+f.}", &config);
+    assert_eq!(square.len(), 1);
+    assert_eq!(square[0].len(), 4);
+
+    // A polygon opened in a branch of another is filled separately.
+    let nested = polygons("{f+f[-{f+f+f}]+f}", &config);
+    assert_eq!(nested.len(), 2);
+    assert_eq!(nested[0].len(), 4);
+    assert!((nested[0][0] - Point::new(-10.0, 10.0)).length() < 1e-4);
+    assert_eq!(nested[1].len(), 4);
+    assert!((nested[1][3] - Point::new(-10.0, 0.0)).length() < 1e-4);
+
+    assert!(polygons("{f}", &config).is_empty());
+}
+
+#[test]
+fn test_fill_color() {
+    let source = "[config]\nfill_color = #ff000080\n\n[rules]\n\n[start]\naxiom = {f+f+f}\n";
+    let (config, system) = parser::parse_str(source).unwrap();
+    let state = system.clone().into_iter().next().unwrap();
+
+    assert_eq!(config.fill_color, Rgba::new(255, 0, 0, 128));
+    assert!(matches!(
+        &turtle::interpret(&state, &config)[0],
+        Primitive::Polygon { color, .. } if *color == config.fill_color
+    ));
+    assert!(parser::write(&config, &system).contains("fill_color = #ff000080\n"));
+    assert!(parser::parse_str(&source.replace("#ff000080", "red")).is_err());
+}
@@ -0,0 +1,46 @@
+use l_system::export::Png;
+use l_system::graphics::turtle;
+use l_system::parser;
+use l_system::system::State;
+
+#[test]
+fn test_stream_matches_iteration() {
+    for name in ["branch", "stochastic", "parametric", "context", "bush"] {
+        let (_, system) = parser::parse(&format!("systems/{}.lsys", name)).unwrap();
+        for (generation, state) in system.clone().into_iter().take(5).enumerate() {
+            let streamed: State = system.clone().stream(generation).collect();
+            assert_eq!(streamed, state, "{} at generation {}", name, generation);
+        }
+    }
+}
+
+#[test]
+fn test_stream_interpretation() {
+    let (config, system) = parser::parse("systems/stochastic.lsys").unwrap();
+    let state = system.clone().into_iter().nth(4).unwrap();
+    assert_eq!(
+        turtle::interpret(system.stream(4), &config),
+        turtle::interpret(&state, &config)
+    );
+}
+
+#[test]
+fn test_stream_png() {
+    let (config, system) = parser::parse("systems/stochastic.lsys").unwrap();
+    let state = system.clone().into_iter().nth(4).unwrap();
+    let png = Png {
+        width: 64,
+        height: 64,
+        ..Png::default()
+    };
+    // Fills, under the lines, are kept from the first walk.
+    let square = parser::parse_state("F{f+f+f+f}F").unwrap();
+    assert_eq!(
+        png.render(&square, &config),
+        png.render_primitives(&turtle::interpret(&square, &config))
+    );
+    assert_eq!(
+        png.render(system.stream(4), &config),
+        png.render_primitives(&turtle::interpret(&state, &config))
+    );
+}
@@ -1,4 +1,5 @@
-use l_system::system::{dump, Symbol, System};
+use l_system::parser;
+use l_system::system::{dump, Expression, Production, Rules, State, Symbol, System};
 use l_system::{state, symbol};
 use std::collections::HashMap;
 
@@ -55,7 +56,7 @@ fn test_fractal_tree() {
 fn test_koch_curve() {
     let mut rules = HashMap::new();
     rules.insert(symbol!('F'), state!("F+F-F-F+F"));
-    let start = vec![symbol!('F')];
+    let start = vec![symbol!('F').into()];
 
     let system = System::new(rules, start);
     let iterator = system.into_iter();
@@ -75,3 +76,113 @@ F+F-F-F+F+F+F-F-F+F-F+F-F-F+F-F+F-F-F+F+F+F-F-F+F",
 
     assert_eq!(actual, expected);
 }
+
+fn stochastic_rules() -> Rules {
+    let mut rules = HashMap::new();
+    rules.insert(
+        symbol!('F'),
+        vec![
+            Production::weighted(state!("F[+F]F[-F]F"), 0.33),
+            Production::weighted(state!("F[+F]F"), 0.33),
+            Production::weighted(state!("F[-F]F"), 0.34),
+        ],
+    );
+    rules
+}
+
+#[test]
+fn test_stochastic_same_seed() {
+    let expand = |seed| {
+        System::stochastic(stochastic_rules(), state!("F"), seed)
+            .into_iter()
+            .nth(4)
+            .map(|s| dump(&s))
+    };
+
+    assert_eq!(expand(42), expand(42));
+    assert_ne!(expand(42), expand(43));
+}
+
+#[test]
+fn test_stochastic_weights() {
+    let mut rules = HashMap::new();
+    rules.insert(
+        symbol!('A'),
+        vec![
+            Production::weighted(state!("B"), 3.0),
+            Production::weighted(state!("C"), 1.0),
+            Production::weighted(state!("D"), 0.0),
+        ],
+    );
+    let start = state!("A".repeat(4000));
+
+    let system = System::stochastic(rules, start, 7);
+    let state = system.into_iter().nth(1).unwrap();
+    let count = |c| state.iter().filter(|s| s.symbol == symbol!(c)).count();
+
+    assert_eq!(count('D'), 0);
+    assert!((2850..3150).contains(&count('B')));
+    assert!((850..1150).contains(&count('C')));
+}
+
+#[test]
+fn test_context_signal() {
+    let mut rules = HashMap::new();
+    rules.insert(
+        symbol!('a'),
+        vec![Production::new(state!("b")).with_context(state!("b"), state!(""))],
+    );
+    rules.insert(symbol!('b'), vec![Production::new(state!("a"))]);
+
+    let system = System::stochastic(rules, state!("baaaa"), 0);
+    let iterator = system.into_iter();
+
+    let expected = vec!["baaaa", "abaaa", "aabaa", "aaaba", "aaaab", "aaaaa"];
+
+    let actual: Vec<_> = iterator.take(expected.len()).map(|s| dump(&s)).collect();
+
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn test_context_branches() {
+    let mut rules = HashMap::new();
+    rules.insert(
+        symbol!('B'),
+        vec![Production::new(state!("X")).with_context(state!("A"), state!(""))],
+    );
+    rules.insert(
+        symbol!('C'),
+        vec![Production::new(state!("Y")).with_context(state!("A"), state!(""))],
+    );
+    rules.insert(
+        symbol!('A'),
+        vec![Production::new(state!("Z")).with_context(state!(""), state!("C"))],
+    );
+    rules.insert(
+        symbol!('D'),
+        vec![Production::new(state!("W")).with_context(state!("B"), state!(""))],
+    );
+
+    let system = System::stochastic(rules, state!("A[B+D]+C"), 0).with_ignore(state!("+"));
+    let actual = system.into_iter().nth(1).unwrap();
+
+    assert_eq!(dump(&actual), "Z[X+W]+Y");
+}
+
+#[test]
+fn test_parametric_context() {
+    let mut rules = HashMap::new();
+    let mut production = Production::new(State::new());
+    production.left = parser::parse_patterns("A(x)").unwrap();
+    production.params = vec!["y".to_string()];
+    production.condition = Some(Expression::parse("x > 0", &production.parameters()).unwrap());
+    production.successor = parser::parse_templates("B(x+y)", &production.parameters()).unwrap();
+    rules.insert(symbol!('B'), vec![production]);
+
+    let start = parser::parse_state("A(1)B(2)A(0)B(2)").unwrap();
+    let system = System::stochastic(rules, start, 0);
+    let actual = system.into_iter().nth(1).unwrap();
+
+    assert_eq!(dump(&actual), "A(1)B(3)A(0)B(2)");
+}
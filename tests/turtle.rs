@@ -0,0 +1,85 @@
+mod common;
+
+use common::{assert_close, segments};
+use l_system::graphics::turtle::{self, Point};
+use l_system::graphics::Config;
+use l_system::parser;
+use l_system::state;
+use l_system::system::State;
+
+#[test]
+fn test_square_closes() {
+    let state: State = state!("F+F+F+F");
+    let config = Config::new(10, 1.0, 1.1, 90.0, 45.0);
+    let segments = segments(&turtle::interpret(&state, &config));
+
+    assert_eq!(segments.len(), 4);
+    assert_close(segments[0].1, Point::new(0.0, 10.0));
+    assert_close(segments[1].1, Point::new(-10.0, 10.0));
+    assert_close(segments[3].1, Point::new(0.0, 0.0));
+}
+
+#[test]
+fn test_branch_depth() {
+    let state: State = state!("F[+F[-F]]F");
+    let config = Config::new(1, 1.0, 1.1, 90.0, 45.0);
+    let depths: Vec<_> = segments(&turtle::interpret(&state, &config))
+        .iter()
+        .map(|s| s.3)
+        .collect();
+
+    assert_eq!(depths, vec![0, 1, 2, 0]);
+}
+
+#[test]
+fn test_parameters_override_config() {
+    let state = parser::parse_state("F(3)-(90)F(2)").unwrap();
+    let config = Config::new(10, 1.0, 1.1, 45.0, 45.0);
+    let segments = segments(&turtle::interpret(&state, &config));
+
+    assert_close(segments[0].1, Point::new(0.0, 3.0));
+    assert_close(segments[1].1, Point::new(2.0, 3.0));
+}
+
+#[test]
+fn test_3d_frame() {
+    let config = Config::new(10, 1.0, 1.1, 90.0, 45.0).three_dimensional();
+    let end = |source| {
+        let state = parser::parse_state(source).unwrap();
+        segments(&turtle::interpret(&state, &config))[0].1
+    };
+
+    assert_close(end("^F"), Point::xyz(0.0, 0.0, 10.0));
+    assert_close(end("&F"), Point::xyz(0.0, 0.0, -10.0));
+    assert_close(end("\\+F"), Point::xyz(0.0, 0.0, 10.0));
+    assert_close(end("/+F"), Point::xyz(0.0, 0.0, -10.0));
+    // Rolled off the horizontal, `$` levels L again so `^` points up.
+    assert_close(end("+\\(30)$^F"), Point::xyz(0.0, 10.0, 0.0));
+    assert_close(end("+\\(30)^F"), Point::xyz(0.0, 5.0, 8.660254));
+}
+
+#[test]
+fn test_swap_is_2d_only() {
+    let config = Config::new(10, 1.0, 1.1, 90.0, 45.0);
+    for (source, end) in [
+        ("&F", Point::new(0.0, 10.0)),
+        ("^F", Point::new(0.0, 10.0)),
+        ("\\+F", Point::new(-10.0, 0.0)),
+        ("/+F", Point::new(-10.0, 0.0)),
+        ("$F", Point::new(0.0, 10.0)),
+    ] {
+        let state: State = state!(source);
+        let segments = segments(&turtle::interpret(&state, &config));
+        assert_close(segments[0].1, end);
+    }
+}
+
+#[test]
+fn test_bush_is_3d() {
+    let (config, system) = parser::parse("systems/bush.lsys").unwrap();
+    let state = system.into_iter().nth(3).unwrap();
+    let segments = segments(&turtle::interpret(&state, &config));
+
+    assert!(config.is_3d());
+    assert!(segments.iter().any(|(_, to, ..)| to.z.abs() > 1.0));
+}
@@ -0,0 +1,22 @@
+use l_system::graphics::Watcher;
+use std::fs;
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn test_watcher() {
+    let path = std::env::temp_dir().join("l_system_watcher.lsys");
+    fs::write(&path, "[start]\nF").unwrap();
+    let mut watcher = Watcher::new(&path);
+    assert!(!watcher.changed());
+
+    // Leave room for filesystems with coarse timestamps.
+    thread::sleep(Duration::from_millis(20));
+    fs::write(&path, "[start]\nFF").unwrap();
+    assert!(watcher.changed());
+    assert!(!watcher.changed());
+
+    fs::remove_file(&path).unwrap();
+    assert!(watcher.changed());
+    assert!(!watcher.changed());
+}